@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use crate::{Environment, MigrationMode};
+
+// Command line flags shared by the backend and ws binaries.
+// Every flag can also come from the environment variable named next to it,
+// clap gives the flag priority when both are set.
+#[derive(Parser, Debug, Default)]
+#[command(version, about = "tic-tac-toe server configuration")]
+pub struct CliArgs {
+    /// Path to a TOML config file (defaults to ./config.toml when it exists)
+    #[arg(long = "config", env = "CONFIG_FILE")]
+    pub config_file: Option<PathBuf>,
+
+    #[arg(long = "env", env = "APP_ENV", value_enum)]
+    pub environment: Option<Environment>,
+
+    #[arg(long, env = "DATABASE_URL")]
+    pub database_url: Option<String>,
+
+    #[arg(long, env = "DB_MAX_CONNECTIONS")]
+    pub db_max_connections: Option<u32>,
+
+    #[arg(long, env = "DB_MIGRATIONS", value_enum)]
+    pub db_migrations: Option<MigrationMode>,
+
+    #[arg(long, env = "BACKEND_BIND")]
+    pub backend_bind: Option<String>,
+
+    #[arg(long, env = "WS_BIND")]
+    pub ws_bind: Option<String>,
+
+    #[arg(long, env = "SECRET_KEY")]
+    pub jwt_secret: Option<String>,
+
+    #[arg(long, env = "JWT_EXPIRY_SECS")]
+    pub jwt_expiry_secs: Option<u64>,
+
+    #[arg(long, env = "WS_HEARTBEAT_INTERVAL_SECS")]
+    pub ws_heartbeat_interval_secs: Option<u64>,
+
+    #[arg(long, env = "WS_CLIENT_TIMEOUT_SECS")]
+    pub ws_client_timeout_secs: Option<u64>,
+
+    #[arg(long, env = "ADMIN_TOKEN")]
+    pub admin_token: Option<String>,
+}
@@ -0,0 +1,228 @@
+use std::{net::SocketAddr, path::Path, time::Duration};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+pub mod cli;
+pub use cli::*;
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+// Settings for every crate in the workspace.
+// Values are layered, each step overrides the one before it:
+// built-in defaults -> TOML file -> environment variables -> command line flags
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub environment: Environment,
+    pub database: DatabaseConfig,
+    pub backend: BackendConfig,
+    pub ws: WsConfig,
+    pub jwt: JwtConfig,
+    pub admin: AdminConfig,
+}
+
+// The binary loading the config , each one only checks the settings it uses
+// (the ws server and the migrate binary have no use for jwt.secret).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    Backend,
+    Ws,
+    Migrate,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    #[default]
+    Development,
+    Production,
+}
+
+// What Db::new does with the embedded migrations at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationMode {
+    Apply, //run pending migrations
+    Check, //only verify , refuse to start when something is pending
+    Off,   //don't touch the schema at all
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub migrations: Option<MigrationMode>, //unset means apply in development , check in production
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(), //no default , must be configured
+            max_connections: 5,
+            migrations: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackendConfig {
+    pub bind: String,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self { bind: "0.0.0.0:3000".into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WsConfig {
+    pub bind: String,
+    pub heartbeat_interval_secs: u64, //how often the server pings each client
+    pub client_timeout_secs: u64,     //drop a client that has not answered for this long
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            bind: "0.0.0.0:3001".into(),
+            heartbeat_interval_secs: 5,
+            client_timeout_secs: 10,
+        }
+    }
+}
+
+impl WsConfig {
+    pub fn heartbeat_interval(&self) -> Duration {
+        Duration::from_secs(self.heartbeat_interval_secs)
+    }
+
+    pub fn client_timeout(&self) -> Duration {
+        Duration::from_secs(self.client_timeout_secs)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JwtConfig {
+    pub secret: String,
+    pub expiry_secs: u64,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            secret: String::new(), //no default , must be configured
+            expiry_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdminConfig {
+    pub token: Option<String>, //admin endpoints are disabled while this is unset
+}
+
+impl Config {
+    /// Load the config for a binary: `.env`, then the config file, env vars and flags.
+    pub fn load(service: Service) -> Result<Self> {
+        dotenvy::dotenv().ok(); //.env is optional , real env vars win over it
+        Self::from_args(CliArgs::parse(), service)
+    }
+
+    pub fn from_args(args: CliArgs, service: Service) -> Result<Self> {
+        let mut config = match &args.config_file {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            None => Self::default(),
+        };
+        config.apply_args(args);
+        config.validate(service)?;
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        toml::from_str(&text)
+            .with_context(|| format!("failed to parse config file {}", path.display()))
+    }
+
+    //the explicit setting wins , otherwise production never changes the schema on its own
+    pub fn migration_mode(&self) -> MigrationMode {
+        self.database.migrations.unwrap_or(match self.environment {
+            Environment::Development => MigrationMode::Apply,
+            Environment::Production => MigrationMode::Check,
+        })
+    }
+
+    //flags and env vars (clap already merged those two) override the file
+    fn apply_args(&mut self, args: CliArgs) {
+        if let Some(v) = args.environment { self.environment = v; }
+        if let Some(v) = args.db_migrations { self.database.migrations = Some(v); }
+        if let Some(v) = args.admin_token { self.admin.token = Some(v); }
+        if let Some(v) = args.database_url { self.database.url = v; }
+        if let Some(v) = args.db_max_connections { self.database.max_connections = v; }
+        if let Some(v) = args.backend_bind { self.backend.bind = v; }
+        if let Some(v) = args.ws_bind { self.ws.bind = v; }
+        if let Some(v) = args.jwt_secret { self.jwt.secret = v; }
+        if let Some(v) = args.jwt_expiry_secs { self.jwt.expiry_secs = v; }
+        if let Some(v) = args.ws_heartbeat_interval_secs { self.ws.heartbeat_interval_secs = v; }
+        if let Some(v) = args.ws_client_timeout_secs { self.ws.client_timeout_secs = v; }
+    }
+
+    pub fn validate(&self, service: Service) -> Result<()> {
+        if self.database.url.is_empty() {
+            bail!("database.url is not set (use DATABASE_URL or --database-url)");
+        }
+        if !["postgres://", "postgresql://", "sqlite:"].iter().any(|s| self.database.url.starts_with(s)) {
+            bail!("database.url must be a postgres:// or sqlite: url");
+        }
+        if self.database.max_connections == 0 {
+            bail!("database.max_connections must be at least 1");
+        }
+        if service == Service::Migrate {
+            return Ok(());
+        }
+
+        let backend_bind: SocketAddr = self.backend.bind
+            .parse()
+            .with_context(|| format!("backend.bind is not a valid address: {}", self.backend.bind))?;
+        let ws_bind: SocketAddr = self.ws.bind
+            .parse()
+            .with_context(|| format!("ws.bind is not a valid address: {}", self.ws.bind))?;
+        if backend_bind.port() == ws_bind.port() && backend_bind.port() != 0 {
+            bail!("backend.bind and ws.bind use the same port {}", ws_bind.port());
+        }
+
+        match service {
+            Service::Backend => {
+                if self.jwt.secret.is_empty() {
+                    bail!("jwt.secret is not set (use SECRET_KEY or --jwt-secret)");
+                }
+                if self.jwt.expiry_secs == 0 {
+                    bail!("jwt.expiry_secs must be greater than 0");
+                }
+                if self.admin.token.as_deref() == Some("") {
+                    bail!("admin.token must not be empty (leave it unset to disable admin endpoints)");
+                }
+            }
+            Service::Ws => {
+                if self.ws.heartbeat_interval_secs == 0 {
+                    bail!("ws.heartbeat_interval_secs must be greater than 0");
+                }
+                if self.ws.client_timeout_secs <= self.ws.heartbeat_interval_secs {
+                    bail!("ws.client_timeout_secs must be longer than ws.heartbeat_interval_secs");
+                }
+            }
+            Service::Migrate => {}
+        }
+        Ok(())
+    }
+}
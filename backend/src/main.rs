@@ -1,4 +1,8 @@
-use actix_web::{App, HttpServer, web,};
+use std::sync::Mutex;
+
+use actix_web::{App, HttpServer, web};
+use ::game::Solver;
+use app_config::{Config, Service};
 pub mod routes;
 pub use routes::*;
 pub mod models;
@@ -7,20 +11,23 @@ pub use middleware::*;
 
 #[actix_web::main]
 async fn main(){
-    dotenvy::dotenv().unwrap();
-    let db = db::Db::new()
+    //RUST_LOG picks the level , info unless set
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let config = Config::load(Service::Backend).expect("Invalid configuration");
+    let db = db::AnyDb::connect(&config.database, config.migration_mode())
         .await
         .expect("Failed to connect to database");
+    let bind = config.backend.bind.clone();
+    //one solver for all workers , /analysis and game reviews fill its table once for everybody
+    let solver = web::Data::new(Mutex::new(Solver::new()));
     let _ = HttpServer::new(move || {  //move || makes a closure that captures the db variable so each worker thread gets a clone.
         App::new()
-            .service(web::resource("/signup").route(web::post().to(create_user)))
-            .service(web::resource("/signin").route(web::post().to(sign_in)))
-            .service(web::resource("/create_room").route(web::post().to(create_room)))
-            .service(web::resource("/get_room").route(web::get().to(get_room)))
-            .service(web::resource("/join_room").route(web::post().to(join_rooms)))
+            .configure(configure::<db::AnyDb>)
             .app_data(actix_web::web::Data::new(db.clone()))
+            .app_data(actix_web::web::Data::new(config.clone()))
+            .app_data(solver.clone())
     })
-    .bind("0.0.0.0:3000")
+    .bind(bind)
     .unwrap()
     .run()
     .await;
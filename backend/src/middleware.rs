@@ -1,8 +1,9 @@
-use std::{env, future::{Ready, ready}};
+use std::future::{Ready, ready};
 
 use actix_web::{
-    dev::Payload, error::ErrorUnauthorized,  FromRequest, HttpRequest,
+    dev::Payload, error::{ErrorInternalServerError, ErrorUnauthorized},  FromRequest, HttpRequest, web::Data,
 };
+use app_config::Config;
 use jsonwebtoken::{DecodingKey, Validation, decode};
 
 use crate::models::Claims;
@@ -16,20 +17,22 @@ impl FromRequest for JwtClaims {
         fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
         let auth_header = req.headers().get("Authorization");
 
-        if let Some(header_value) = auth_header {
-            if let Ok(token) = header_value.to_str() {
-                let secret = env::var("SECRET_KEY").expect("JWT_SECRET must be set");
-                let decoding_key = DecodingKey::from_secret(secret.as_bytes());
-                let validation = Validation::default();
+        if let Some(header_value) = auth_header
+            && let Ok(token) = header_value.to_str() {
+            let Some(config) = req.app_data::<Data<Config>>() else {
+                return ready(Err(ErrorInternalServerError("config not registered")));
+            };
+            let secret = &config.jwt.secret;
+            let decoding_key = DecodingKey::from_secret(secret.as_bytes());
+            let validation = Validation::default();
 
-                match decode::<Claims>(token, &decoding_key, &validation) {
-                    Ok(token_data) => {
-                        return ready(Ok(JwtClaims(token_data.claims)));
-                    }
-                    Err(e) => {
-                        eprintln!("JWT decoding error: {:?}", e);
-                        return ready(Err(ErrorUnauthorized("Invalid JWT token")));
-                    }
+            match decode::<Claims>(token, &decoding_key, &validation) {
+                Ok(token_data) => {
+                    return ready(Ok(JwtClaims(token_data.claims)));
+                }
+                Err(e) => {
+                    log::warn!("JWT decoding error: {:?}", e);
+                    return ready(Err(ErrorUnauthorized("Invalid JWT token")));
                 }
             }
         }
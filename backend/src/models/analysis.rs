@@ -0,0 +1,44 @@
+use game::{Analysis, CellMove, GameReview, mcts::MctsResult};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize,Deserialize,Clone,Copy,Default,PartialEq)]
+#[serde(rename_all="lowercase")]
+pub enum Engine{
+    #[default]
+    Solver, //exact , only for boards small enough to search completely
+    Mcts    //monte carlo tree search , an estimate that works on any board
+}
+
+#[derive(Serialize,Deserialize)]
+pub struct AnalysisRequest{
+    pub position : String, //compact notation , e.g. "X1O/3/3 X playing 3x3k3" or "X1O/3/3 X playing 3x3k3-misere"
+    #[serde(default)]
+    pub engine : Engine,
+    #[serde(default)]
+    pub iterations : Option<u32>, //mcts only , playouts to run
+    #[serde(default)]
+    pub time_ms : Option<u64> //mcts only , time budget
+}
+
+#[derive(Serialize,Deserialize)]
+#[serde(untagged)]
+pub enum EngineAnalysis{
+    Solver(Analysis),
+    Mcts(MctsResult<CellMove>) //moves as `{"position":4}` , with the `piece` in wild positions
+}
+
+#[derive(Serialize,Deserialize)]
+pub struct AnalysisResponse{
+    pub position : String, //the position as we read it , normalised
+    pub engine : Engine,
+    #[serde(flatten)]
+    pub analysis : EngineAnalysis
+}
+
+#[derive(Serialize,Deserialize)]
+pub struct GameReviewResponse{
+    pub room_id : Uuid,
+    pub players : Vec<Uuid>, //players[0] played X
+    pub review : GameReview
+}
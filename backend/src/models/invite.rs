@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize,Deserialize)]
+pub struct CreateInviteRequest{
+    pub room_id : Uuid,
+    #[serde(default)]
+    pub ttl_minutes : Option<i64> //how long the invite stays valid , defaults to a day
+}
+
+#[derive(Serialize,Deserialize)]
+pub struct InviteResponse{
+    pub code : String,
+    pub room_id : Uuid,
+    pub expires_at : DateTime<Utc>,
+    pub link : String,
+    pub has_password : bool
+}
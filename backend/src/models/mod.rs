@@ -2,3 +2,7 @@ pub mod user;
 pub use user::*;
 pub mod room;
 pub use room::*;
+pub mod invite;
+pub use invite::*;
+pub mod analysis;
+pub use analysis::*;
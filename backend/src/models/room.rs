@@ -1,3 +1,4 @@
+use game::{GameKind, RuleSet};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -7,8 +8,23 @@ pub struct UserRoomRequest{
 }
 
 
+#[derive(Serialize,Deserialize)]
+pub struct CreateRoomRequest{
+    pub id : Uuid,
+    #[serde(default)]
+    pub password : Option<String>, //makes the room private
+    #[serde(default)]
+    pub game : GameKind, //tic_tac_toe unless asked otherwise
+    #[serde(default)]
+    pub rules : RuleSet //standard unless asked otherwise , misere and notakto work for tic_tac_toe rows
+}
+
 #[derive(Serialize,Deserialize)]
 pub struct  UserJoinRoomRequest{
     pub room_id : Uuid,
-    pub player_o_id :Uuid
+    pub player_o_id :Uuid,
+    #[serde(default)]
+    pub password : Option<String>,
+    #[serde(default)]
+    pub version : Option<i32> //room version the client last saw , 409 when it changed
 }
\ No newline at end of file
@@ -18,6 +18,12 @@ pub struct SigninResponse {
     pub token: String
 }
 
+#[derive(Serialize,Deserialize)]
+pub struct RatingResponse {
+    pub username: String,
+    pub rating: i32
+}
+
 #[derive(Serialize,Deserialize)]
 pub struct Claims {
     pub sub : Uuid,
@@ -26,10 +32,11 @@ pub struct Claims {
 }
 
 impl Claims{
-    pub fn new(sub:Uuid) ->Self{
+    //token is valid for expiry_secs from now
+    pub fn new(sub:Uuid,expiry_secs:u64) ->Self{
         Self { 
             sub,
-            exp:1000000000000,
+            exp:(chrono::Utc::now().timestamp() as u64 + expiry_secs) as usize,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,34 @@
+use actix_web::{HttpRequest, web::{Data, Json}, Error};
+use app_config::Config;
+use db::{MigrationStatus, Repo};
+use subtle::ConstantTimeEq;
+
+//admin routes are only reachable with the configured X-Admin-Token header
+fn check_admin(req: &HttpRequest, config: &Config) -> Result<(), Error> {
+    let Some(expected) = &config.admin.token else {
+        return Err(actix_web::error::ErrorNotFound("Admin endpoints are disabled"));
+    };
+    let given = req.headers().get("X-Admin-Token").map(|v| v.as_bytes()).unwrap_or_default();
+    //constant time so the token can't be guessed a byte at a time from response timings
+    if !bool::from(given.ct_eq(expected.as_bytes())) {
+        return Err(actix_web::error::ErrorUnauthorized("Invalid admin token"));
+    }
+    Ok(())
+}
+
+pub async fn get_migrations<R: Repo>(
+    req: HttpRequest,
+    db: Data<R>,
+    config: Data<Config>,
+) -> Result<Json<Vec<MigrationStatus>>, Error> {
+    check_admin(&req, &config)?;
+
+    let status = db
+        .migration_status()
+        .await
+        .map_err(|e| {
+            log::error!("DB Error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Failed to read migrations")
+        })?;
+    Ok(Json(status))
+}
@@ -0,0 +1,58 @@
+use std::{sync::Mutex, time::Duration};
+
+use actix_web::{web::{self, Json}, Error};
+use game::{GameState, Solver, mcts::{self, MctsConfig}};
+use crate::models::{AnalysisRequest, AnalysisResponse, Engine, EngineAnalysis};
+
+//limits for one mcts request , the search runs on the blocking pool
+const MAX_ITERATIONS: u32 = 200_000;
+const MAX_TIME_MS: u64 = 5_000;
+const MCTS_THREADS: usize = 2;
+
+//solve a position , needs no storage so it isn't generic over the repo.
+//the solver is shared by every request (see main) so a position is only solved once
+pub async fn analyze_position(
+    body: Json<AnalysisRequest>,
+    solver: web::Data<Mutex<Solver>>,
+) -> Result<Json<AnalysisResponse>, Error> {
+    let body = body.into_inner();
+    let game: GameState = body
+        .position
+        .parse()
+        .map_err(|e: String| actix_web::error::ErrorBadRequest(format!("Invalid position: {}", e)))?;
+
+    let analysis = match body.engine {
+        Engine::Solver => {
+            let position = game.clone();
+            let analysis = web::block(move || solver.lock().unwrap_or_else(|e| e.into_inner()).analyze(&position))
+                .await
+                .map_err(|e| {
+                    log::error!("Solver Error: {:?}", e);
+                    actix_web::error::ErrorInternalServerError("Search failed")
+                })?;
+            EngineAnalysis::Solver(analysis)
+        }
+        Engine::Mcts => {
+            let mut search = game.clone();
+            search.start(); //a waiting game is analysed as if it had just started , like the solver does
+            let config = MctsConfig {
+                iterations: body.iterations.map(|n| n.min(MAX_ITERATIONS)),
+                time_budget: body.time_ms.map(|ms| Duration::from_millis(ms.min(MAX_TIME_MS))),
+                threads: MCTS_THREADS,
+                ..Default::default()
+            };
+            let result = web::block(move || mcts::search(&search, &config))
+                .await
+                .map_err(|e| {
+                    log::error!("Search Error: {:?}", e);
+                    actix_web::error::ErrorInternalServerError("Search failed")
+                })?;
+            EngineAnalysis::Mcts(result)
+        }
+    };
+    Ok(Json(AnalysisResponse {
+        position: game.to_string(),
+        engine: body.engine,
+        analysis,
+    }))
+}
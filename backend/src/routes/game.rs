@@ -0,0 +1,184 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use actix_web::{HttpResponse, http::header::ContentDisposition, web::{self, Data, Json, Path}, Error};
+use db::{Repo, models::{GameReplay, StoredEvent}};
+use game::{CellMove, Solver};
+use uuid::Uuid;
+
+use crate::models::GameReviewResponse;
+
+async fn load_replay<R: Repo>(db: &R, room_id: Uuid) -> Result<Option<GameReplay>, Error> {
+    let events = db
+        .get_events(room_id)
+        .await
+        .map_err(|e| {
+            log::error!("DB Error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Failed to load game")
+        })?;
+
+    replay_log(room_id, &events)
+}
+
+fn replay_log(room_id: Uuid, events: &[StoredEvent]) -> Result<Option<GameReplay>, Error> {
+    GameReplay::from_events(room_id, events)
+        .map_err(|e| {
+            log::error!("Replay error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Failed to rebuild game")
+        })
+}
+
+//only finished games can be looked at , a live game would give away nothing new but isn't history yet
+async fn finished_game<R: Repo>(db: &R, room_id: Uuid) -> Result<GameReplay, Error> {
+    let replay = load_replay(db, room_id)
+        .await?
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Game not found"))?;
+
+    if !replay.status.is_finished() {
+        return Err(actix_web::error::ErrorConflict("Game is still in progress"));
+    }
+    Ok(replay)
+}
+
+//usernames of everybody who played `replays` , looked up in one query
+async fn player_names<R: Repo>(db: &R, replays: &[GameReplay]) -> Result<HashMap<Uuid, String>, Error> {
+    let mut ids: Vec<Uuid> = replays.iter().flat_map(|r| r.players.iter().copied()).collect();
+    ids.sort();
+    ids.dedup();
+    let users = db
+        .get_users_by_ids(&ids)
+        .await
+        .map_err(|e| {
+            log::error!("DB Error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Failed to load players")
+        })?;
+    Ok(users.into_iter().map(|u| (u.id, u.username)).collect())
+}
+
+//username for a player tag , ws players that never signed up keep their id
+fn player_name(names: &HashMap<Uuid, String>, id: Option<&Uuid>) -> String {
+    match id {
+        Some(id) => names.get(id).cloned().unwrap_or_else(|| id.to_string()),
+        None => "?".to_string(),
+    }
+}
+
+//the record format and the solver only know the 3x3 board
+fn cell_moves(replay: &GameReplay) -> Result<Vec<CellMove>, Error> {
+    replay
+        .cell_moves()
+        .map_err(|e| actix_web::error::ErrorUnprocessableEntity(e.to_string()))
+}
+
+//names of everybody on `seat` , teammates joined with " & "
+fn seat_name(names: &HashMap<Uuid, String>, replay: &GameReplay, seat: usize) -> String {
+    let players = replay.seat_players(seat);
+    if players.is_empty() {
+        return player_name(names, None);
+    }
+    players.iter().map(|id| player_name(names, Some(id))).collect::<Vec<_>>().join(" & ")
+}
+
+fn to_record(names: &HashMap<Uuid, String>, replay: &GameReplay) -> Result<String, Error> {
+    let x = seat_name(names, replay, 0);
+    let o = seat_name(names, replay, 1);
+    replay
+        .to_record(&x, &o)
+        .map(|record| record.to_string())
+        .map_err(|e| actix_web::error::ErrorUnprocessableEntity(e.to_string()))
+}
+
+fn pgn_response(body: String, filename: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .insert_header(ContentDisposition::attachment(filename))
+        .body(body)
+}
+
+//every move of a finished game with the board after it , built from the ws event log
+pub async fn get_replay<R: Repo>(
+    db: Data<R>,
+    path: Path<Uuid>,
+) -> Result<Json<GameReplay>, Error> {
+    let replay = finished_game(db.get_ref(), path.into_inner()).await?;
+    Ok(Json(replay))
+}
+
+//every move graded against perfect play , with accuracy per player.
+//uses the solver /analysis shares (see main) , the search runs on the blocking pool
+pub async fn get_review<R: Repo>(
+    db: Data<R>,
+    solver: Data<Mutex<Solver>>,
+    path: Path<Uuid>,
+) -> Result<Json<GameReviewResponse>, Error> {
+    let replay = finished_game(db.get_ref(), path.into_inner()).await?;
+
+    let moves = cell_moves(&replay)?;
+    let rules = replay.rules;
+    let review = web::block(move || solver.lock().unwrap_or_else(|e| e.into_inner()).review(rules, &moves))
+        .await
+        .map_err(|e| {
+            log::error!("Review error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Failed to review game")
+        })?
+        .map_err(|e| {
+            log::error!("Review error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Failed to review game")
+        })?;
+
+    Ok(Json(GameReviewResponse {
+        room_id: replay.room_id,
+        players: replay.players,
+        review,
+    }))
+}
+
+//the game as a portable text record
+pub async fn get_game_record<R: Repo>(
+    db: Data<R>,
+    path: Path<Uuid>,
+) -> Result<HttpResponse, Error> {
+    let room_id = path.into_inner();
+    let replay = finished_game(db.get_ref(), room_id).await?;
+    let names = player_names(db.get_ref(), std::slice::from_ref(&replay)).await?;
+    let record = to_record(&names, &replay)?;
+    Ok(pgn_response(record, format!("{}.pgn", room_id)))
+}
+
+//all finished games of a user in one file , oldest first
+pub async fn export_user_games<R: Repo>(
+    db: Data<R>,
+    path: Path<String>,
+) -> Result<HttpResponse, Error> {
+    let username = path.into_inner();
+    let user = db
+        .get_user_by_username(&username)
+        .await
+        .map_err(|_| actix_web::error::ErrorNotFound("User not found"))?;
+
+    //every room's log in one query , they come back room by room
+    let events = db
+        .get_user_events(user.id)
+        .await
+        .map_err(|e| {
+            log::error!("DB Error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Failed to load games")
+        })?;
+
+    let mut replays = Vec::new();
+    for log in events.chunk_by(|a, b| a.room_id == b.room_id) {
+        let Some(replay) = replay_log(log[0].room_id, log)? else {
+            continue;
+        };
+        //a reused room id only keeps its latest game , skip it if the user wasn't in that one
+        //(games off the 3x3 board have no record format)
+        if !replay.status.is_finished() || !replay.players.contains(&user.id) || replay.cell_moves().is_err() {
+            continue;
+        }
+        replays.push(replay);
+    }
+
+    //every opponent's name in one query , not one per game
+    let names = player_names(db.get_ref(), &replays).await?;
+    let records = replays.iter().map(|replay| to_record(&names, replay)).collect::<Result<Vec<_>, _>>()?;
+    Ok(pgn_response(records.join("\n"), format!("{}.pgn", username)))
+}
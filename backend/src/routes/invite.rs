@@ -0,0 +1,91 @@
+use actix_web::{web::{Data, Json, Path}, Error};
+use chrono::Duration;
+use db::{Repo, models::RoomInvite};
+use crate::{JwtClaims, models::{CreateInviteRequest, InviteResponse}};
+
+const DEFAULT_INVITE_TTL_MINUTES: i64 = 24 * 60;
+const MAX_INVITE_TTL_MINUTES: i64 = 7 * 24 * 60;
+
+fn invite_response(invite: RoomInvite, has_password: bool) -> InviteResponse {
+    InviteResponse {
+        link: format!("/invites/{}", invite.code),
+        code: invite.code,
+        room_id: invite.room_id,
+        expires_at: invite.expires_at,
+        has_password,
+    }
+}
+
+//only the player who created the room can hand out invites for it
+pub async fn create_invite<R: Repo>(
+    db: Data<R>,
+    claims: JwtClaims,
+    body: Json<CreateInviteRequest>,
+) -> Result<Json<InviteResponse>, Error> {
+    let room = db
+        .get_room_by_room_id(body.room_id)
+        .await
+        .map_err(|e| {
+            log::error!("DB Error: {:?}", e);
+            actix_web::error::ErrorNotFound("Room not found")
+        })?;
+
+    if room.player_x_id != claims.0.sub {
+        return Err(actix_web::error::ErrorForbidden("Only the room creator can invite players"));
+    }
+
+    let ttl = body.ttl_minutes.unwrap_or(DEFAULT_INVITE_TTL_MINUTES);
+    if !(1..=MAX_INVITE_TTL_MINUTES).contains(&ttl) {
+        return Err(actix_web::error::ErrorBadRequest("ttl_minutes must be between 1 and 10080"));
+    }
+
+    let invite = db
+        .create_invite(room.id, claims.0.sub, Duration::minutes(ttl))
+        .await
+        .map_err(|e| {
+            log::error!("DB Error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Failed to create invite")
+        })?;
+
+    Ok(Json(invite_response(invite, room.password.is_some())))
+}
+
+//resolve an invite link to its room , used by clients before joining
+pub async fn get_invite<R: Repo>(
+    db: Data<R>,
+    code: Path<String>,
+) -> Result<Json<InviteResponse>, Error> {
+    let invite = db
+        .get_active_invite(&code)
+        .await
+        .map_err(|e| actix_web::error::ErrorNotFound(e.to_string()))?;
+
+    let room = db
+        .get_room_by_room_id(invite.room_id)
+        .await
+        .map_err(|e| {
+            log::error!("DB Error: {:?}", e);
+            actix_web::error::ErrorNotFound("Room not found")
+        })?;
+
+    Ok(Json(invite_response(invite, room.password.is_some())))
+}
+
+pub async fn revoke_invite<R: Repo>(
+    db: Data<R>,
+    claims: JwtClaims,
+    code: Path<String>,
+) -> Result<Json<InviteResponse>, Error> {
+    let invite = db
+        .revoke_invite(&code, claims.0.sub)
+        .await
+        .map_err(|e| actix_web::error::ErrorNotFound(e.to_string()))?;
+
+    let has_password = db
+        .get_room_by_room_id(invite.room_id)
+        .await
+        .map(|room| room.password.is_some())
+        .unwrap_or(false);
+
+    Ok(Json(invite_response(invite, has_password)))
+}
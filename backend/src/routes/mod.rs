@@ -1,4 +1,39 @@
+use actix_web::web;
+use db::Repo;
+
 pub mod user;
 pub use user::*;
 pub mod room;
-pub use room::*;
\ No newline at end of file
+pub use room::*;
+pub mod invite;
+pub use invite::*;
+pub mod admin;
+pub use admin::*;
+pub mod game;
+pub use game::*;
+pub mod analysis;
+pub use analysis::*;
+#[cfg(test)]
+mod tests;
+
+//every route of the API , generic over the storage so tests can mount it on MemoryDb
+pub fn configure<R: Repo>(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("/signup").route(web::post().to(create_user::<R>)))
+        .service(web::resource("/signin").route(web::post().to(sign_in::<R>)))
+        .service(web::resource("/create_room").route(web::post().to(create_room::<R>)))
+        .service(web::resource("/get_room").route(web::get().to(get_room::<R>)))
+        .service(web::resource("/join_room").route(web::post().to(join_rooms::<R>)))
+        .service(web::resource("/invites").route(web::post().to(create_invite::<R>)))
+        .service(
+            web::resource("/invites/{code}")
+                .route(web::get().to(get_invite::<R>))
+                .route(web::delete().to(revoke_invite::<R>))
+        )
+        .service(web::resource("/games/{id}/replay").route(web::get().to(get_replay::<R>)))
+        .service(web::resource("/games/{id}/review").route(web::get().to(get_review::<R>)))
+        .service(web::resource("/games/{id}.pgn").route(web::get().to(get_game_record::<R>)))
+        .service(web::resource("/users/{username}/rating").route(web::get().to(get_rating::<R>)))
+        .service(web::resource("/users/{username}/games.pgn").route(web::get().to(export_user_games::<R>)))
+        .service(web::resource("/analysis").route(web::post().to(analyze_position)))
+        .service(web::resource("/admin/migrations").route(web::get().to(get_migrations::<R>)));
+}
@@ -1,46 +1,83 @@
-use actix_web::{web::{Data, Json}, Error};
-use db::{Db, models::Room};
-use crate::models::{ UserJoinRoomRequest, UserRoomRequest};
+use actix_web::{web::{self, Data, Json}, Error};
+use db::{Repo, models::Room};
+use crate::models::{ CreateRoomRequest, UserJoinRoomRequest, UserRoomRequest};
 
-pub async fn create_room(
-    db: Data<Db>,
-    body: Json<UserRoomRequest>,
+pub async fn create_room<R: Repo>(
+    db: Data<R>,
+    body: Json<CreateRoomRequest>,
 ) -> Result<Json<Room>, Error> {
+    //room rows have two seats , bigger games and most rule sets are set up over the websocket
+    db::models::check_rules(body.game, body.rules).map_err(|e| actix_web::error::ErrorBadRequest(e.to_string()))?;
+
+    //only the argon2 hash is stored , hashing is slow so it runs on the blocking pool
+    let password = match body.password.clone() {
+        Some(password) => Some(
+            web::block(move || db::hash_password(&password))
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?
+                .map_err(|e| {
+                    log::error!("Hash Error: {:?}", e);
+                    actix_web::error::ErrorInternalServerError("Failed to create room")
+                })?,
+        ),
+        None => None,
+    };
 
     let room = db
-        .create_room(body.id)
+        .create_room(body.id, password.as_deref(), body.game, body.rules)
         .await
         .map_err(|e| {
-            println!("DB Error: {:?}", e);
+            log::error!("DB Error: {:?}", e);
             actix_web::error::ErrorInternalServerError("Failed to create room")
         })?;
 
     Ok(Json(room))
 }
 
-pub async fn get_room(
-    db:Data<Db>,
+pub async fn get_room<R: Repo>(
+    db:Data<R>,
     body: Json<UserRoomRequest>
 )->Result<Json<Room>,Error>{
     let room = db
         .get_room_by_room_id(body.id)
         .await
         .map_err(|e|{
-            print!("DB error : {:?}",e);
+            log::error!("DB error : {:?}",e);
             actix_web::error::ErrorInternalServerError("Failed to get room")
         })?;
     Ok(Json(room))
 }
 
-pub async fn join_rooms(
-    db:Data<Db>,
+pub async fn join_rooms<R: Repo>(
+    db:Data<R>,
     body: Json<UserJoinRoomRequest>
 )->Result<Json<Room>,Error>{
     let room = db
-        .join_room(body.room_id,body.player_o_id)
+        .get_room_by_room_id(body.room_id)
+        .await
+        .map_err(|e|{
+            log::error!("DB error : {:?}",e);
+            actix_web::error::ErrorNotFound("Room not found")
+        })?;
+    if let Some(hash) = room.password.clone() {
+        let given = body.password.clone().unwrap_or_default();
+        let matches = web::block(move || db::verify_password(&given, &hash))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        if !matches {
+            return Err(actix_web::error::ErrorForbidden("Incorrect room password"));
+        }
+    }
+
+    let room = db
+        .join_room(body.room_id,body.player_o_id,body.version)
         .await
         .map_err(|e|{
-            print!("DB error : {:?}",e);
+            log::error!("DB error : {:?}",e);
+            //the room changed since the client read it , it should refetch and retry
+            if db::is_conflict(&e) {
+                return actix_web::error::ErrorConflict(e.to_string());
+            }
             actix_web::error::ErrorInternalServerError("Failde to get room")
         })?;
     Ok(Json(room))
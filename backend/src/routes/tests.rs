@@ -0,0 +1,258 @@
+use std::sync::Mutex;
+
+use actix_web::{App, dev::{Service, ServiceResponse}, http::StatusCode, test, web::Data};
+use ::game::{GameEvent, GameKind, GameRecord, GameResult, Mark, MovePayload, RoomStatus, RuleSet, Solver, INITIAL_RATING};
+use app_config::Config;
+use db::{EventRepo, MemoryDb, RoomRepo, MigrationStatus, models::{GameReplay, Room}};
+use serde_json::json;
+use uuid::Uuid;
+
+use super::configure;
+use crate::models::{InviteResponse, RatingResponse, SigninResponse, UserResponse};
+
+const ADMIN_TOKEN: &str = "let-me-in";
+
+fn config() -> Config {
+    let mut config = Config::default();
+    config.jwt.secret = "test-secret".into();
+    config.jwt.expiry_secs = 3600;
+    config.admin.token = Some(ADMIN_TOKEN.into());
+    config
+}
+
+//the API as main mounts it , on storage the test can also reach directly
+async fn app(db: &MemoryDb) -> impl Service<actix_http::Request, Response = ServiceResponse, Error = actix_web::Error> {
+    test::init_service(
+        App::new()
+            .configure(configure::<MemoryDb>)
+            .app_data(Data::new(db.clone()))
+            .app_data(Data::new(config()))
+            .app_data(Data::new(Mutex::new(Solver::new())))
+    )
+    .await
+}
+
+async fn call(
+    app: &impl Service<actix_http::Request, Response = ServiceResponse, Error = actix_web::Error>,
+    req: test::TestRequest,
+) -> ServiceResponse {
+    test::call_service(app, req.to_request()).await
+}
+
+//signs a new user up and in , returns their id and token
+async fn sign_up(
+    app: &impl Service<actix_http::Request, Response = ServiceResponse, Error = actix_web::Error>,
+    username: &str,
+) -> (Uuid, String) {
+    let body = json!({"username": username, "password": "pw"});
+    let user: UserResponse = test::read_body_json(call(app, test::TestRequest::post().uri("/signup").set_json(&body)).await).await;
+    let signin: SigninResponse = test::read_body_json(call(app, test::TestRequest::post().uri("/signin").set_json(&body)).await).await;
+    (user.id, signin.token)
+}
+
+fn payload(position: usize) -> MovePayload {
+    match json!({"position": position}) {
+        serde_json::Value::Object(fields) => fields,
+        _ => unreachable!(),
+    }
+}
+
+#[actix_web::test]
+async fn signup_signin_and_rating() {
+    let db = MemoryDb::new();
+    let app = app(&db).await;
+    sign_up(&app, "alice").await;
+
+    let taken = call(&app, test::TestRequest::post().uri("/signup").set_json(json!({"username": "alice", "password": "x"}))).await;
+    assert_eq!(taken.status(), StatusCode::CONFLICT);
+    let wrong = call(&app, test::TestRequest::post().uri("/signin").set_json(json!({"username": "alice", "password": "x"}))).await;
+    assert_eq!(wrong.status(), StatusCode::CONFLICT);
+
+    let rating: RatingResponse = test::read_body_json(call(&app, test::TestRequest::get().uri("/users/alice/rating")).await).await;
+    assert_eq!(rating.rating, INITIAL_RATING);
+    let missing = call(&app, test::TestRequest::get().uri("/users/nobody/rating")).await;
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn joining_checks_the_password_and_version() {
+    let db = MemoryDb::new();
+    let app = app(&db).await;
+    let (alice, _) = sign_up(&app, "alice").await;
+    let (bob, _) = sign_up(&app, "bob").await;
+
+    let room: Room = test::read_body_json(
+        call(&app, test::TestRequest::post().uri("/create_room").set_json(json!({"id": alice, "password": "hunter2"}))).await,
+    )
+    .await;
+    assert_ne!(room.password.as_deref(), Some("hunter2"), "stored in plain text");
+
+    let join = |password: &str, version: i32| {
+        test::TestRequest::post()
+            .uri("/join_room")
+            .set_json(json!({"room_id": room.id, "player_o_id": bob, "password": password, "version": version}))
+    };
+    assert_eq!(call(&app, join("hunter3", room.version)).await.status(), StatusCode::FORBIDDEN);
+    assert_eq!(call(&app, join("hunter2", room.version + 1)).await.status(), StatusCode::CONFLICT);
+
+    let joined: Room = test::read_body_json(call(&app, join("hunter2", room.version)).await).await;
+    assert_eq!(joined.status, RoomStatus::Playing);
+    assert_eq!(joined.player_o_id, Some(bob));
+    assert_eq!(joined.version, room.version + 1);
+}
+
+#[actix_web::test]
+async fn rows_are_only_for_two_player_games() {
+    let db = MemoryDb::new();
+    let app = app(&db).await;
+    let (alice, _) = sign_up(&app, "alice").await;
+
+    for (game, rules) in [("multiplayer", "standard"), ("multiplayer", "elimination"), ("tic_tac_toe", "fog")] {
+        let res = call(&app, test::TestRequest::post().uri("/create_room").set_json(json!({"id": alice, "game": game, "rules": rules}))).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{} {}", game, rules);
+    }
+    //the storage turns them down too , not just the route
+    assert!(db.create_room(alice, None, GameKind::Multiplayer, RuleSet::Standard).await.is_err());
+    assert!(db.create_room(alice, None, GameKind::Qubic, RuleSet::Standard).await.is_ok());
+}
+
+#[actix_web::test]
+async fn only_the_creator_hands_out_and_revokes_invites() {
+    let db = MemoryDb::new();
+    let app = app(&db).await;
+    let (alice, alice_token) = sign_up(&app, "alice").await;
+    let (_, bob_token) = sign_up(&app, "bob").await;
+    let room: Room = test::read_body_json(
+        call(&app, test::TestRequest::post().uri("/create_room").set_json(json!({"id": alice, "password": "hunter2"}))).await,
+    )
+    .await;
+
+    let invite = |token: Option<&str>| {
+        let req = test::TestRequest::post().uri("/invites").set_json(json!({"room_id": room.id}));
+        match token {
+            Some(token) => req.insert_header(("Authorization", token)),
+            None => req,
+        }
+    };
+    assert_eq!(call(&app, invite(None)).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(call(&app, invite(Some("not-a-jwt"))).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(call(&app, invite(Some(&bob_token))).await.status(), StatusCode::FORBIDDEN);
+
+    let created: InviteResponse = test::read_body_json(call(&app, invite(Some(&alice_token))).await).await;
+    assert_eq!(created.room_id, room.id);
+    assert!(created.has_password);
+
+    let uri = format!("/invites/{}", created.code);
+    let found: InviteResponse = test::read_body_json(call(&app, test::TestRequest::get().uri(&uri)).await).await;
+    assert_eq!(found.room_id, room.id);
+
+    let revoke = |token: &str| test::TestRequest::delete().uri(&uri).insert_header(("Authorization", token.to_string()));
+    assert_eq!(call(&app, revoke(&bob_token)).await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(call(&app, revoke(&alice_token)).await.status(), StatusCode::OK);
+    assert_eq!(call(&app, test::TestRequest::get().uri(&uri)).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn analysis_solves_positions_and_rejects_bad_ones() {
+    let db = MemoryDb::new();
+    let app = app(&db).await;
+
+    let analyze = |position: &str| test::TestRequest::post().uri("/analysis").set_json(json!({"position": position}));
+    //X to move with two corners left open forces a win
+    let res = call(&app, analyze("X1O/3/3_X_playing_3x3k3")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let analysis: serde_json::Value = test::read_body_json(res).await;
+    assert_eq!(analysis["position"], "X1O/3/3 X playing 3x3k3");
+    assert_eq!(analysis["outcome"], "win");
+
+    //the rule set comes with the position , the solver plays it
+    for (position, outcome) in [("XX1/1X1/3 O playing 3x3k3-notakto", "win"), ("3/3/3 X waiting 3x3k3-misere", "draw")] {
+        let analysis: serde_json::Value = test::read_body_json(call(&app, analyze(position)).await).await;
+        assert_eq!(analysis["position"], position);
+        assert_eq!(analysis["outcome"], outcome, "{}", position);
+    }
+    //X wins by finishing the row of O , the move has to say so
+    let mcts = json!({"position": "OO1/3/3 X playing 3x3k3-wild", "engine": "mcts", "iterations": 2000});
+    let analysis: serde_json::Value = test::read_body_json(call(&app, test::TestRequest::post().uri("/analysis").set_json(mcts)).await).await;
+    assert_eq!(analysis["best_move"], json!({"position": 2, "piece": "O"}));
+
+    //multiplayer marks used to get as far as the zobrist table and panic there
+    for position in ["Δ2/3/3 X playing 3x3k3", "3/3/3 Δ playing 3x3k3", "nonsense"] {
+        assert_eq!(call(&app, analyze(position)).await.status(), StatusCode::BAD_REQUEST, "{}", position);
+    }
+}
+
+#[actix_web::test]
+async fn finished_games_replay_and_export() {
+    let db = MemoryDb::new();
+    let app = app(&db).await;
+    let (alice, _) = sign_up(&app, "alice").await;
+    let (bob, _) = sign_up(&app, "bob").await;
+
+    //the log the ws server writes , X takes the top row
+    let room_id = Uuid::new_v4();
+    let mut events = vec![
+        GameEvent::Created { private: false, hints: false, game: GameKind::TicTacToe, rules: RuleSet::Standard, players: None, teams: None },
+        GameEvent::Joined { user_id: alice, mark: Mark::X },
+        GameEvent::Joined { user_id: bob, mark: Mark::O },
+    ];
+    for (i, position) in [0, 3, 1, 4, 2].into_iter().enumerate() {
+        let (user_id, mark) = if i % 2 == 0 { (alice, Mark::X) } else { (bob, Mark::O) };
+        events.push(GameEvent::Moved { user_id, mark, mv: payload(position) });
+    }
+
+    let replay_uri = format!("/games/{}/replay", room_id);
+    assert_eq!(call(&app, test::TestRequest::get().uri(&replay_uri)).await.status(), StatusCode::NOT_FOUND);
+    for event in &events[..4] {
+        db.append_event(room_id, event).await.unwrap();
+    }
+    let pgn_uri = format!("/games/{}.pgn", room_id);
+    assert_eq!(call(&app, test::TestRequest::get().uri(&pgn_uri)).await.status(), StatusCode::CONFLICT);
+    for event in &events[4..] {
+        db.append_event(room_id, event).await.unwrap();
+    }
+
+    let replay: GameReplay = test::read_body_json(call(&app, test::TestRequest::get().uri(&replay_uri)).await).await;
+    assert_eq!(replay.players, vec![alice, bob]);
+    assert_eq!(replay.status, RoomStatus::Won);
+    assert_eq!(replay.winner, Some(Mark::X));
+    assert_eq!(replay.moves.len(), 5);
+
+    let review: serde_json::Value = test::read_body_json(call(&app, test::TestRequest::get().uri(&format!("/games/{}/review", room_id))).await).await;
+    assert_eq!(review["players"], json!([alice, bob]));
+
+    //a rematch alice gives up two moves in , no line on the board
+    let rematch = Uuid::new_v4();
+    for event in events[..5].iter().chain([&GameEvent::Resigned { user_id: alice, mark: Mark::X }]) {
+        db.append_event(rematch, event).await.unwrap();
+    }
+
+    let export = test::read_body(call(&app, test::TestRequest::get().uri("/users/bob/games.pgn")).await).await;
+    let records = GameRecord::parse_many(std::str::from_utf8(&export).unwrap()).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].tag("X"), Some("alice"));
+    assert_eq!(records[0].tag("O"), Some("bob"));
+    assert_eq!(records[0].tag("Termination"), Some("normal"));
+    assert_eq!(records[0].result, GameResult::XWins);
+    assert_eq!(records[0].replay().map(|g| g.winner), Ok(Some(Mark::X)));
+
+    //the export reads back in , the resignation included
+    assert_eq!(records[1].tag("Termination"), Some(::game::RESIGNATION));
+    assert_eq!(records[1].moves.len(), 2);
+    assert_eq!(records[1].result, GameResult::OWins);
+    assert_eq!(records[1].replay().map(|g| g.winner), Ok(Some(Mark::O)));
+}
+
+#[actix_web::test]
+async fn migrations_need_the_admin_token() {
+    let db = MemoryDb::new();
+    let app = app(&db).await;
+
+    let migrations = |token: &str| test::TestRequest::get().uri("/admin/migrations").insert_header(("X-Admin-Token", token.to_string()));
+    assert_eq!(call(&app, test::TestRequest::get().uri("/admin/migrations")).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(call(&app, migrations("let-me-out")).await.status(), StatusCode::UNAUTHORIZED);
+
+    let status: Vec<MigrationStatus> = test::read_body_json(call(&app, migrations(ADMIN_TOKEN)).await).await;
+    assert!(!status.is_empty());
+    assert!(status.iter().all(|m| m.applied));
+}
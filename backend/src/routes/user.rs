@@ -1,12 +1,12 @@
-use std::env;
-use actix_web::web::{Data, Json};
-use db::{Db};
+use actix_web::web::{Data, Json, Path};
+use app_config::Config;
+use db::Repo;
 use jsonwebtoken::{EncodingKey, Header, encode};
-use crate::{ models::{Claims, SigninResponse, UserRequest, UserResponse}};
+use crate::{ models::{Claims, RatingResponse, SigninResponse, UserRequest, UserResponse}};
 
 
 
-pub async fn create_user(db: Data<Db>, body: Json<UserRequest>) -> Result<Json<UserResponse>, actix_web::error::Error> {
+pub async fn create_user<R: Repo>(db: Data<R>, body: Json<UserRequest>) -> Result<Json<UserResponse>, actix_web::error::Error> {
     let user = db.create_user(&body.username, &body.password)
         .await
         .map_err(|e| actix_web::error::ErrorConflict(e.to_string()))?;
@@ -16,7 +16,7 @@ pub async fn create_user(db: Data<Db>, body: Json<UserRequest>) -> Result<Json<U
     }))
 }
 
-pub async fn sign_in(db: Data<Db>,body: Json<UserRequest>)->Result<Json<SigninResponse>,actix_web::error::Error>{
+pub async fn sign_in<R: Repo>(db: Data<R>,config: Data<Config>,body: Json<UserRequest>)->Result<Json<SigninResponse>,actix_web::error::Error>{
     let user = db.get_user_by_username(&body.username)
         .await
         .map_err(|e| actix_web::error::ErrorConflict(e.to_string()))?;
@@ -27,12 +27,20 @@ pub async fn sign_in(db: Data<Db>,body: Json<UserRequest>)->Result<Json<SigninRe
 
     let token = encode(
         &Header::default(),
-        &Claims::new(user.id),
-        &EncodingKey::from_secret(
-            env::var("SECRET_KEY")
-               .map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string()))?.as_bytes()
-        )  
+        &Claims::new(user.id, config.jwt.expiry_secs),
+        &EncodingKey::from_secret(config.jwt.secret.as_bytes())
     ).map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string()))?;
     Ok(Json(SigninResponse{token}))
 }
 
+//current elo rating , players start at game::INITIAL_RATING
+pub async fn get_rating<R: Repo>(db: Data<R>, path: Path<String>) -> Result<Json<RatingResponse>, actix_web::error::Error> {
+    let user = db.get_user_by_username(&path.into_inner())
+        .await
+        .map_err(|_| actix_web::error::ErrorNotFound("User not found"))?;
+
+    Ok(Json(RatingResponse{
+        username: user.username,
+        rating: user.rating
+    }))
+}
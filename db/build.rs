@@ -0,0 +1,6 @@
+// sqlx::migrate! embeds db/migrations (and db/migrations_sqlite) at compile time,
+// rebuild when a migration is added or edited.
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+    println!("cargo:rerun-if-changed=migrations_sqlite");
+}
@@ -0,0 +1,15 @@
+ALTER TABLE rooms ADD COLUMN IF NOT EXISTS password VARCHAR(255);
+
+CREATE TABLE IF NOT EXISTS room_invites (
+    code VARCHAR(16) PRIMARY KEY,
+
+    room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    created_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_room_invites_room ON room_invites(room_id);
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS moves (
+    room_id UUID NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    ply INTEGER NOT NULL CHECK (ply >= 1),
+
+    position SMALLINT NOT NULL CHECK (position BETWEEN 0 AND 8),
+    mark CHAR(1) NOT NULL CHECK (mark IN ('X', 'O')),
+    player_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (room_id, ply),
+    UNIQUE (room_id, position)
+);
+
+ALTER TABLE rooms
+    ADD CONSTRAINT rooms_board_state_check CHECK (board_state ~ '^[XO-]{9}$'),
+    ADD CONSTRAINT rooms_next_turn_check CHECK (next_turn IN ('X', 'O')),
+    ADD CONSTRAINT rooms_winner_check CHECK (winner IS NULL OR winner IN ('X', 'O')),
+    ADD CONSTRAINT rooms_status_check CHECK (status IN ('waiting', 'playing', 'won', 'draw'));
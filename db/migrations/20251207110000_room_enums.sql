@@ -0,0 +1,20 @@
+CREATE TYPE mark AS ENUM ('X', 'O');
+CREATE TYPE room_status AS ENUM ('waiting', 'playing', 'won', 'draw');
+
+-- the enum types replace the check constraints added with the moves table
+ALTER TABLE rooms
+    DROP CONSTRAINT rooms_next_turn_check,
+    DROP CONSTRAINT rooms_winner_check,
+    DROP CONSTRAINT rooms_status_check;
+
+ALTER TABLE rooms
+    ALTER COLUMN next_turn DROP DEFAULT,
+    ALTER COLUMN next_turn TYPE mark USING next_turn::text::mark,
+    ALTER COLUMN next_turn SET DEFAULT 'X',
+    ALTER COLUMN winner TYPE mark USING winner::text::mark,
+    ALTER COLUMN status DROP DEFAULT,
+    ALTER COLUMN status TYPE room_status USING status::room_status,
+    ALTER COLUMN status SET DEFAULT 'waiting';
+
+ALTER TABLE moves DROP CONSTRAINT moves_mark_check;
+ALTER TABLE moves ALTER COLUMN mark TYPE mark USING mark::text::mark;
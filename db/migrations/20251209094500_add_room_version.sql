@@ -0,0 +1,2 @@
+-- bumped on every update , writers only succeed against the version they read
+ALTER TABLE rooms ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
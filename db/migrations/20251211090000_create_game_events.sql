@@ -0,0 +1,23 @@
+-- Append-only log of everything that happens in a ws room (see game::GameEvent).
+-- No foreign key to rooms: lobby rooms only live in the ws server.
+CREATE TABLE IF NOT EXISTS game_events (
+    room_id UUID NOT NULL,
+    seq INTEGER NOT NULL CHECK (seq >= 1),
+
+    kind TEXT NOT NULL,
+    data JSONB NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (room_id, seq)
+);
+
+CREATE OR REPLACE FUNCTION game_events_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'game_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER game_events_append_only
+BEFORE UPDATE OR DELETE ON game_events
+FOR EACH ROW EXECUTE FUNCTION game_events_append_only();
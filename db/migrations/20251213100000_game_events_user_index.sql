@@ -0,0 +1,4 @@
+-- finding a user's games (bulk export) looks up the joined events by user id
+CREATE INDEX IF NOT EXISTS idx_game_events_joined_user
+ON game_events ((data->>'user_id'))
+WHERE kind = 'joined';
@@ -0,0 +1,15 @@
+-- rooms can host other games than tic-tac-toe , board_state keeps one character per cell of that game
+CREATE TYPE game_kind AS ENUM ('tic_tac_toe', 'connect_four');
+
+ALTER TABLE rooms ADD COLUMN game game_kind NOT NULL DEFAULT 'tic_tac_toe';
+
+ALTER TABLE rooms DROP CONSTRAINT rooms_board_state_check;
+ALTER TABLE rooms ALTER COLUMN board_state TYPE VARCHAR(42);
+ALTER TABLE rooms ADD CONSTRAINT rooms_board_state_check CHECK (
+    (game = 'tic_tac_toe' AND board_state ~ '^[XO-]{9}$')
+    OR (game = 'connect_four' AND board_state ~ '^[XO-]{42}$')
+);
+
+-- a connect four move is stored as the cell the disc landed on (7 columns x 6 rows)
+ALTER TABLE moves DROP CONSTRAINT moves_position_check;
+ALTER TABLE moves ADD CONSTRAINT moves_position_check CHECK (position BETWEEN 0 AND 41);
@@ -0,0 +1,3 @@
+-- a new enum value can't be used in the transaction that adds it ,
+-- the board checks for it follow in the next migration
+ALTER TYPE game_kind ADD VALUE 'qubic';
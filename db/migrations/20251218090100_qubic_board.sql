@@ -0,0 +1,11 @@
+-- qubic boards are 4x4x4 , board_state keeps one character per cell (x fastest , then y , then z)
+ALTER TABLE rooms DROP CONSTRAINT rooms_board_state_check;
+ALTER TABLE rooms ALTER COLUMN board_state TYPE VARCHAR(64);
+ALTER TABLE rooms ADD CONSTRAINT rooms_board_state_check CHECK (
+    (game = 'tic_tac_toe' AND board_state ~ '^[XO-]{9}$')
+    OR (game = 'connect_four' AND board_state ~ '^[XO-]{42}$')
+    OR (game = 'qubic' AND board_state ~ '^[XO-]{64}$')
+);
+
+ALTER TABLE moves DROP CONSTRAINT moves_position_check;
+ALTER TABLE moves ADD CONSTRAINT moves_position_check CHECK (position BETWEEN 0 AND 63);
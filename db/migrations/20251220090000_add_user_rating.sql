@@ -0,0 +1,2 @@
+-- elo rating , updated when a game played over the websocket ends (see game::rating)
+ALTER TABLE users ADD COLUMN rating INTEGER NOT NULL DEFAULT 1200;
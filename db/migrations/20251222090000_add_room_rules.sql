@@ -0,0 +1,8 @@
+-- rooms remember the rule set they play by , only the ones a board and a position per move
+-- can hold (see check_rules) , the rest are played over the websocket
+CREATE TYPE rule_set AS ENUM (
+    'standard', 'misere', 'wild', 'notakto', 'order_and_chaos',
+    'quantum', 'fog', 'fog_retry', 'elimination'
+);
+
+ALTER TABLE rooms ADD COLUMN rules rule_set NOT NULL DEFAULT 'standard';
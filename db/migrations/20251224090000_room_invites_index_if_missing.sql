@@ -0,0 +1,3 @@
+-- the room invite index , only where it is missing.
+-- 20251203101500 stays as it was first applied , sqlx checks its checksum on every start
+CREATE INDEX IF NOT EXISTS idx_room_invites_room ON room_invites(room_id);
@@ -0,0 +1,19 @@
+-- sqlite version of migrations/20251130063358_create_room.sql
+CREATE TABLE IF NOT EXISTS rooms (
+    id BLOB PRIMARY KEY NOT NULL,
+
+    player_x_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    player_o_id BLOB REFERENCES users(id) ON DELETE CASCADE,
+
+    board_state TEXT NOT NULL DEFAULT '---------',
+    next_turn TEXT NOT NULL DEFAULT 'X',
+
+    winner TEXT,
+    status TEXT NOT NULL DEFAULT 'waiting',
+
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_rooms_player_x ON rooms(player_x_id);
+CREATE INDEX idx_rooms_player_o ON rooms(player_o_id);
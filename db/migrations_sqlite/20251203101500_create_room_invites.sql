@@ -0,0 +1,16 @@
+-- sqlite version of migrations/20251203101500_create_room_invites.sql
+ALTER TABLE rooms ADD COLUMN password TEXT;
+
+CREATE TABLE IF NOT EXISTS room_invites (
+    code TEXT PRIMARY KEY NOT NULL,
+
+    room_id BLOB NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    created_by BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    expires_at TEXT NOT NULL,
+    revoked_at TEXT,
+
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_room_invites_room ON room_invites(room_id);
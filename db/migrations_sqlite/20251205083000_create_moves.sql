@@ -0,0 +1,38 @@
+-- sqlite version of migrations/20251205083000_create_moves.sql
+CREATE TABLE IF NOT EXISTS moves (
+    room_id BLOB NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    ply INTEGER NOT NULL CHECK (ply >= 1),
+
+    position INTEGER NOT NULL CHECK (position BETWEEN 0 AND 8),
+    mark TEXT NOT NULL CHECK (mark IN ('X', 'O')),
+    player_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    created_at TEXT NOT NULL,
+
+    PRIMARY KEY (room_id, ply),
+    UNIQUE (room_id, position)
+);
+
+-- sqlite can't add CHECK constraints to an existing table (and rebuilding rooms
+-- would cascade into room_invites) , so the rooms checks are triggers
+CREATE TRIGGER rooms_check_insert BEFORE INSERT ON rooms
+WHEN NOT (
+    length(NEW.board_state) = 9 AND NEW.board_state NOT GLOB '*[^XO-]*'
+    AND NEW.next_turn IN ('X', 'O')
+    AND (NEW.winner IS NULL OR NEW.winner IN ('X', 'O'))
+    AND NEW.status IN ('waiting', 'playing', 'won', 'draw')
+)
+BEGIN
+    SELECT RAISE(ABORT, 'rooms check constraint failed');
+END;
+
+CREATE TRIGGER rooms_check_update BEFORE UPDATE ON rooms
+WHEN NOT (
+    length(NEW.board_state) = 9 AND NEW.board_state NOT GLOB '*[^XO-]*'
+    AND NEW.next_turn IN ('X', 'O')
+    AND (NEW.winner IS NULL OR NEW.winner IN ('X', 'O'))
+    AND NEW.status IN ('waiting', 'playing', 'won', 'draw')
+)
+BEGIN
+    SELECT RAISE(ABORT, 'rooms check constraint failed');
+END;
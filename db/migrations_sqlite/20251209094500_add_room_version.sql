@@ -0,0 +1,2 @@
+-- sqlite version of migrations/20251209094500_add_room_version.sql
+ALTER TABLE rooms ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,22 @@
+-- sqlite version of migrations/20251211090000_create_game_events.sql
+CREATE TABLE IF NOT EXISTS game_events (
+    room_id BLOB NOT NULL,
+    seq INTEGER NOT NULL CHECK (seq >= 1),
+
+    kind TEXT NOT NULL,
+    data TEXT NOT NULL,
+
+    created_at TEXT NOT NULL,
+
+    PRIMARY KEY (room_id, seq)
+);
+
+CREATE TRIGGER game_events_no_update BEFORE UPDATE ON game_events
+BEGIN
+    SELECT RAISE(ABORT, 'game_events is append-only');
+END;
+
+CREATE TRIGGER game_events_no_delete BEFORE DELETE ON game_events
+BEGIN
+    SELECT RAISE(ABORT, 'game_events is append-only');
+END;
@@ -0,0 +1,4 @@
+-- sqlite version of migrations/20251213100000_game_events_user_index.sql
+CREATE INDEX IF NOT EXISTS idx_game_events_joined_user
+ON game_events (json_extract(data, '$.user_id'))
+WHERE kind = 'joined';
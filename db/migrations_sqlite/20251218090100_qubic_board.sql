@@ -0,0 +1,53 @@
+-- sqlite version of migrations/20251218090000_add_qubic_game_kind.sql and migrations/20251218090100_qubic_board.sql
+-- (game is plain text here , so there is no enum to extend first)
+DROP TRIGGER rooms_check_insert;
+DROP TRIGGER rooms_check_update;
+
+CREATE TRIGGER rooms_check_insert BEFORE INSERT ON rooms
+WHEN NOT (
+    ((NEW.game = 'tic_tac_toe' AND length(NEW.board_state) = 9)
+        OR (NEW.game = 'connect_four' AND length(NEW.board_state) = 42)
+        OR (NEW.game = 'qubic' AND length(NEW.board_state) = 64))
+    AND NEW.board_state NOT GLOB '*[^XO-]*'
+    AND NEW.next_turn IN ('X', 'O')
+    AND (NEW.winner IS NULL OR NEW.winner IN ('X', 'O'))
+    AND NEW.status IN ('waiting', 'playing', 'won', 'draw')
+)
+BEGIN
+    SELECT RAISE(ABORT, 'rooms check constraint failed');
+END;
+
+CREATE TRIGGER rooms_check_update BEFORE UPDATE ON rooms
+WHEN NOT (
+    ((NEW.game = 'tic_tac_toe' AND length(NEW.board_state) = 9)
+        OR (NEW.game = 'connect_four' AND length(NEW.board_state) = 42)
+        OR (NEW.game = 'qubic' AND length(NEW.board_state) = 64))
+    AND NEW.board_state NOT GLOB '*[^XO-]*'
+    AND NEW.next_turn IN ('X', 'O')
+    AND (NEW.winner IS NULL OR NEW.winner IN ('X', 'O'))
+    AND NEW.status IN ('waiting', 'playing', 'won', 'draw')
+)
+BEGIN
+    SELECT RAISE(ABORT, 'rooms check constraint failed');
+END;
+
+-- rebuild moves for the wider position range , like the room game migration did
+CREATE TABLE moves_new (
+    room_id BLOB NOT NULL REFERENCES rooms(id) ON DELETE CASCADE,
+    ply INTEGER NOT NULL CHECK (ply >= 1),
+
+    position INTEGER NOT NULL CHECK (position BETWEEN 0 AND 63),
+    mark TEXT NOT NULL CHECK (mark IN ('X', 'O')),
+    player_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    created_at TEXT NOT NULL,
+
+    PRIMARY KEY (room_id, ply),
+    UNIQUE (room_id, position)
+);
+
+INSERT INTO moves_new (room_id, ply, position, mark, player_id, created_at)
+SELECT room_id, ply, position, mark, player_id, created_at FROM moves;
+
+DROP TABLE moves;
+ALTER TABLE moves_new RENAME TO moves;
@@ -0,0 +1,2 @@
+-- sqlite version of migrations/20251220090000_add_user_rating.sql
+ALTER TABLE users ADD COLUMN rating INTEGER NOT NULL DEFAULT 1200;
@@ -0,0 +1,5 @@
+-- sqlite version of migrations/20251222090000_add_room_rules.sql
+ALTER TABLE rooms ADD COLUMN rules TEXT NOT NULL DEFAULT 'standard' CHECK (rules IN (
+    'standard', 'misere', 'wild', 'notakto', 'order_and_chaos',
+    'quantum', 'fog', 'fog_retry', 'elimination'
+));
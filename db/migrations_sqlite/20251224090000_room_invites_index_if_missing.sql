@@ -0,0 +1,2 @@
+-- sqlite version of migrations/20251224090000_room_invites_index_if_missing.sql
+CREATE INDEX IF NOT EXISTS idx_room_invites_room ON room_invites(room_id);
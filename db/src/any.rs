@@ -0,0 +1,205 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use app_config::{DatabaseConfig, MigrationMode};
+use game::{GameEvent, GameKind, Mark, RuleSet};
+use uuid::Uuid;
+
+use crate::{
+    Db, EventRepo, InviteRepo, MigrationRepo, MigrationStatus, MoveRepo, RoomRepo, UserRepo,
+    models::{CreateUserResponse, Move, Room, RoomInvite, StoredEvent, User},
+};
+#[cfg(feature = "sqlite")]
+use crate::SqliteDb;
+
+// The storage picked at startup from the DATABASE_URL scheme.
+// postgres:// -> Db , sqlite: -> SqliteDb (needs the `sqlite` feature)
+#[derive(Clone)]
+pub enum AnyDb {
+    Postgres(Db),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteDb),
+}
+
+impl AnyDb {
+    pub async fn connect(config: &DatabaseConfig, migrations: MigrationMode) -> Result<Self> {
+        if config.url.starts_with("sqlite:") {
+            #[cfg(feature = "sqlite")]
+            return Ok(Self::Sqlite(SqliteDb::new(config, migrations).await?));
+            #[cfg(not(feature = "sqlite"))]
+            anyhow::bail!("sqlite DATABASE_URL given but the db crate was built without the `sqlite` feature");
+        }
+        Ok(Self::Postgres(Db::new(config, migrations).await?))
+    }
+
+    pub async fn run_migrations(&self) -> Result<()> {
+        match self {
+            Self::Postgres(db) => db.run_migrations().await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.run_migrations().await,
+        }
+    }
+}
+
+impl UserRepo for AnyDb {
+    async fn create_user(&self, username: &str, password: &str) -> Result<CreateUserResponse> {
+        match self {
+            Self::Postgres(db) => db.create_user(username, password).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.create_user(username, password).await,
+        }
+    }
+
+    async fn get_user_by_username(&self, username: &str) -> Result<User> {
+        match self {
+            Self::Postgres(db) => db.get_user_by_username(username).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.get_user_by_username(username).await,
+        }
+    }
+
+    async fn get_user_by_id(&self, id: Uuid) -> Result<User> {
+        match self {
+            Self::Postgres(db) => db.get_user_by_id(id).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.get_user_by_id(id).await,
+        }
+    }
+
+    async fn get_users_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        match self {
+            Self::Postgres(db) => db.get_users_by_ids(ids).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.get_users_by_ids(ids).await,
+        }
+    }
+
+    async fn update_ratings(&self, seats: &[Vec<Uuid>], places: &[usize]) -> Result<Vec<Vec<i32>>> {
+        match self {
+            Self::Postgres(db) => db.update_ratings(seats, places).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.update_ratings(seats, places).await,
+        }
+    }
+}
+
+impl RoomRepo for AnyDb {
+    async fn create_room(&self, player_x_id: Uuid, password: Option<&str>, game: GameKind, rules: RuleSet) -> Result<Room> {
+        match self {
+            Self::Postgres(db) => db.create_room(player_x_id, password, game, rules).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.create_room(player_x_id, password, game, rules).await,
+        }
+    }
+
+    async fn get_room_by_room_id(&self, room_id: Uuid) -> Result<Room> {
+        match self {
+            Self::Postgres(db) => db.get_room_by_room_id(room_id).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.get_room_by_room_id(room_id).await,
+        }
+    }
+
+    async fn join_room(&self, room_id: Uuid, player_o_id: Uuid, expected_version: Option<i32>) -> Result<Room> {
+        match self {
+            Self::Postgres(db) => db.join_room(room_id, player_o_id, expected_version).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.join_room(room_id, player_o_id, expected_version).await,
+        }
+    }
+
+    async fn finish_room(&self, room_id: Uuid, winner: Option<Mark>) -> Result<Room> {
+        match self {
+            Self::Postgres(db) => db.finish_room(room_id, winner).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.finish_room(room_id, winner).await,
+        }
+    }
+}
+
+impl InviteRepo for AnyDb {
+    async fn create_invite(&self, room_id: Uuid, created_by: Uuid, ttl: Duration) -> Result<RoomInvite> {
+        match self {
+            Self::Postgres(db) => db.create_invite(room_id, created_by, ttl).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.create_invite(room_id, created_by, ttl).await,
+        }
+    }
+
+    async fn get_active_invite(&self, code: &str) -> Result<RoomInvite> {
+        match self {
+            Self::Postgres(db) => db.get_active_invite(code).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.get_active_invite(code).await,
+        }
+    }
+
+    async fn revoke_invite(&self, code: &str, user_id: Uuid) -> Result<RoomInvite> {
+        match self {
+            Self::Postgres(db) => db.revoke_invite(code, user_id).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.revoke_invite(code, user_id).await,
+        }
+    }
+}
+
+impl MoveRepo for AnyDb {
+    async fn apply_move(&self, room_id: Uuid, player_id: Uuid, position: usize, expected_version: Option<i32>) -> Result<(Room, Move)> {
+        match self {
+            Self::Postgres(db) => db.apply_move(room_id, player_id, position, expected_version).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.apply_move(room_id, player_id, position, expected_version).await,
+        }
+    }
+
+    async fn get_moves(&self, room_id: Uuid) -> Result<Vec<Move>> {
+        match self {
+            Self::Postgres(db) => db.get_moves(room_id).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.get_moves(room_id).await,
+        }
+    }
+}
+
+impl EventRepo for AnyDb {
+    async fn append_event(&self, room_id: Uuid, event: &GameEvent) -> Result<StoredEvent> {
+        match self {
+            Self::Postgres(db) => db.append_event(room_id, event).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.append_event(room_id, event).await,
+        }
+    }
+
+    async fn get_events(&self, room_id: Uuid) -> Result<Vec<StoredEvent>> {
+        match self {
+            Self::Postgres(db) => db.get_events(room_id).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.get_events(room_id).await,
+        }
+    }
+
+    async fn get_user_events(&self, user_id: Uuid) -> Result<Vec<StoredEvent>> {
+        match self {
+            Self::Postgres(db) => db.get_user_events(user_id).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.get_user_events(user_id).await,
+        }
+    }
+
+    async fn get_active_rooms(&self, since: DateTime<Utc>) -> Result<Vec<Uuid>> {
+        match self {
+            Self::Postgres(db) => db.get_active_rooms(since).await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.get_active_rooms(since).await,
+        }
+    }
+}
+
+impl MigrationRepo for AnyDb {
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        match self {
+            Self::Postgres(db) => db.migration_status().await,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite(db) => db.migration_status().await,
+        }
+    }
+}
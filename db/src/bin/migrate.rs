@@ -0,0 +1,42 @@
+use clap::Parser;
+use app_config::{CliArgs, Config, MigrationMode, Service};
+use db::{AnyDb, MigrationRepo};
+
+// cargo run -p db --bin migrate            -> apply pending migrations
+// cargo run -p db --bin migrate -- --check -> only list them , exit 1 when something is pending
+#[derive(Parser)]
+#[command(about = "Apply or check the embedded database migrations")]
+struct MigrateCli {
+    #[arg(long)]
+    check: bool,
+
+    #[command(flatten)]
+    config: CliArgs,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    let cli = MigrateCli::parse();
+    let config = Config::from_args(cli.config, Service::Migrate)?;
+    let db = AnyDb::connect(&config.database, MigrationMode::Off).await?;
+
+    if !cli.check {
+        db.run_migrations().await?;
+    }
+
+    let status = db.migration_status().await?;
+    for m in &status {
+        println!(
+            "{} {:>14} {}",
+            if m.applied { "applied" } else { "pending" },
+            m.version,
+            m.description
+        );
+    }
+
+    if status.iter().any(|m| !m.applied) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+
+use uuid::Uuid;
+
+/// Returned (inside anyhow::Error) when a room was changed by someone else
+/// between reading it and writing it. Callers should refetch and retry.
+#[derive(Debug)]
+pub struct VersionConflict {
+    pub room_id: Uuid,
+    pub expected: i32,
+    pub actual: i32,
+}
+
+impl fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "room {} was modified concurrently (expected version {}, found {})",
+            self.room_id, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for VersionConflict {}
+
+//true when the error (or anything it wraps) is a version conflict
+pub fn is_conflict(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<VersionConflict>().is_some()
+}
@@ -0,0 +1,307 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Duration, Utc};
+use game::{GameEvent, GameKind, INITIAL_RATING, Mark, RoomStatus, RuleSet, rate_teams};
+use uuid::Uuid;
+
+use crate::{
+    EventRepo, InviteRepo, MIGRATOR, MigrationRepo, MigrationStatus, MoveRepo, RoomRepo, UserRepo,
+    models::{CreateUserResponse, Move, Room, RoomInvite, StoredEvent, User, check_rules, check_version, finished_status, join_error, moved_event, new_invite_code, plan_move},
+};
+
+// In-memory storage with the same behaviour as the postgres queries.
+// Meant for tests and for running the HTTP API without a database.
+// Clones share the same data, like clones of Db share one pool.
+#[derive(Clone, Default)]
+pub struct MemoryDb {
+    inner: Arc<Mutex<MemoryState>>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    users: HashMap<Uuid, User>,
+    rooms: HashMap<Uuid, Room>,
+    invites: HashMap<String, RoomInvite>,
+    moves: HashMap<Uuid, Vec<Move>>,
+    events: HashMap<Uuid, Vec<StoredEvent>>,
+}
+
+impl MemoryDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MemoryState> {
+        // a panic while holding the lock can't leave the maps half-written , keep going
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl UserRepo for MemoryDb {
+    async fn create_user(&self, username: &str, password: &str) -> Result<CreateUserResponse> {
+        let mut state = self.state();
+        if state.users.values().any(|u| u.username == username) {
+            bail!("duplicate key value violates unique constraint \"users_username_key\"");
+        }
+        let user = User {
+            id: Uuid::new_v4(),
+            username: username.to_string(),
+            password: password.to_string(),
+            rating: INITIAL_RATING,
+        };
+        let id = user.id;
+        state.users.insert(id, user);
+        Ok(CreateUserResponse { id })
+    }
+
+    async fn get_user_by_username(&self, username: &str) -> Result<User> {
+        self.state()
+            .users
+            .values()
+            .find(|u| u.username == username)
+            .cloned()
+            .ok_or_else(|| anyhow!("no rows returned by a query that expected to return at least one row"))
+    }
+
+    async fn get_user_by_id(&self, id: Uuid) -> Result<User> {
+        self.state()
+            .users
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| anyhow!("no rows returned by a query that expected to return at least one row"))
+    }
+
+    async fn get_users_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        let state = self.state();
+        Ok(ids.iter().filter_map(|id| state.users.get(id).cloned()).collect())
+    }
+
+    async fn update_ratings(&self, seats: &[Vec<Uuid>], places: &[usize]) -> Result<Vec<Vec<i32>>> {
+        let mut state = self.state();
+        let ratings: Vec<Vec<i32>> = seats
+            .iter()
+            .map(|seat| seat.iter().map(|id| state.users.get(id).map_or(INITIAL_RATING, |u| u.rating)).collect())
+            .collect();
+        let rated = rate_teams(&ratings, places);
+        for (id, rating) in seats.iter().flatten().zip(rated.iter().flatten()) {
+            if let Some(user) = state.users.get_mut(id) {
+                user.rating = *rating;
+            }
+        }
+        Ok(rated)
+    }
+}
+
+impl RoomRepo for MemoryDb {
+    async fn create_room(&self, player_x_id: Uuid, password: Option<&str>, game: GameKind, rules: RuleSet) -> Result<Room> {
+        check_rules(game, rules)?;
+        let mut state = self.state();
+        if !state.users.contains_key(&player_x_id) {
+            bail!("insert or update on table \"rooms\" violates foreign key constraint");
+        }
+        let now = Utc::now();
+        let room = Room {
+            id: Uuid::new_v4(),
+            player_x_id,
+            player_o_id: None,
+            board_state: game.empty_cells(),
+            next_turn: Mark::X,
+            winner: None,
+            status: RoomStatus::Waiting,
+            game,
+            rules,
+            password: password.map(str::to_string),
+            version: 1,
+            created_at: now,
+            updated_at: now,
+        };
+        state.rooms.insert(room.id, room.clone());
+        Ok(room)
+    }
+
+    async fn get_room_by_room_id(&self, room_id: Uuid) -> Result<Room> {
+        self.state()
+            .rooms
+            .get(&room_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("no rows returned by a query that expected to return at least one row"))
+    }
+
+    async fn join_room(&self, room_id: Uuid, player_o_id: Uuid, expected_version: Option<i32>) -> Result<Room> {
+        let mut state = self.state();
+        let current = state.rooms.get(&room_id).cloned();
+        let expected = expected_version.or(current.as_ref().map(|r| r.version));
+        let room = state
+            .rooms
+            .get_mut(&room_id)
+            .filter(|r| Some(r.version) == expected && r.player_o_id.is_none() && r.status == RoomStatus::Waiting)
+            .ok_or_else(|| join_error(room_id, current, expected.unwrap_or_default()))?;
+        room.player_o_id = Some(player_o_id);
+        room.status = RoomStatus::Playing;
+        room.version += 1;
+        room.updated_at = Utc::now();
+        Ok(room.clone())
+    }
+
+    async fn finish_room(&self, room_id: Uuid, winner: Option<Mark>) -> Result<Room> {
+        let mut state = self.state();
+        let room = state
+            .rooms
+            .get_mut(&room_id)
+            .filter(|r| r.status == RoomStatus::Playing)
+            .ok_or_else(|| anyhow!("Failed to finish room: game is not in progress"))?;
+        room.status = finished_status(winner);
+        room.winner = winner;
+        room.version += 1;
+        room.updated_at = Utc::now();
+        Ok(room.clone())
+    }
+}
+
+impl InviteRepo for MemoryDb {
+    async fn create_invite(&self, room_id: Uuid, created_by: Uuid, ttl: Duration) -> Result<RoomInvite> {
+        let mut state = self.state();
+        let mut code = new_invite_code();
+        while state.invites.contains_key(&code) {
+            code = new_invite_code();
+        }
+        let now = Utc::now();
+        let invite = RoomInvite {
+            code: code.clone(),
+            room_id,
+            created_by,
+            expires_at: now + ttl,
+            revoked_at: None,
+            created_at: now,
+        };
+        state.invites.insert(code, invite.clone());
+        Ok(invite)
+    }
+
+    async fn get_active_invite(&self, code: &str) -> Result<RoomInvite> {
+        let now = Utc::now();
+        self.state()
+            .invites
+            .get(&code.to_uppercase())
+            .filter(|i| i.revoked_at.is_none() && i.expires_at > now)
+            .cloned()
+            .ok_or_else(|| anyhow!("Invite not found or expired"))
+    }
+
+    async fn revoke_invite(&self, code: &str, user_id: Uuid) -> Result<RoomInvite> {
+        let mut state = self.state();
+        let invite = state
+            .invites
+            .get_mut(&code.to_uppercase())
+            .filter(|i| i.created_by == user_id && i.revoked_at.is_none())
+            .ok_or_else(|| anyhow!("Failed to revoke invite"))?;
+        invite.revoked_at = Some(Utc::now());
+        Ok(invite.clone())
+    }
+}
+
+impl MoveRepo for MemoryDb {
+    async fn apply_move(&self, room_id: Uuid, player_id: Uuid, position: usize, expected_version: Option<i32>) -> Result<(Room, Move)> {
+        let mut state = self.state();
+        let room = state
+            .rooms
+            .get_mut(&room_id)
+            .ok_or_else(|| anyhow!("no rows returned by a query that expected to return at least one row"))?;
+
+        check_version(room, expected_version)?;
+        let plan = plan_move(room, player_id, position)?;
+        let now = Utc::now();
+        room.board_state = plan.board_state;
+        room.next_turn = plan.next_turn;
+        room.winner = plan.winner;
+        room.status = plan.status;
+        room.version += 1;
+        room.updated_at = now;
+        let room = room.clone();
+
+        let mv = Move {
+            room_id,
+            ply: plan.ply,
+            position: plan.position as i32,
+            mark: plan.mark,
+            player_id,
+            created_at: now,
+        };
+        state.moves.entry(room_id).or_default().push(mv.clone());
+        let log = state.events.entry(room_id).or_default();
+        log.push(StoredEvent {
+            room_id,
+            seq: log.len() as i32 + 1,
+            data: moved_event(&room, &mv),
+            created_at: now,
+        });
+        Ok((room, mv))
+    }
+
+    async fn get_moves(&self, room_id: Uuid) -> Result<Vec<Move>> {
+        Ok(self.state().moves.get(&room_id).cloned().unwrap_or_default())
+    }
+}
+
+impl EventRepo for MemoryDb {
+    async fn append_event(&self, room_id: Uuid, event: &GameEvent) -> Result<StoredEvent> {
+        let mut state = self.state();
+        let log = state.events.entry(room_id).or_default();
+        let stored = StoredEvent {
+            room_id,
+            seq: log.len() as i32 + 1,
+            data: event.clone(),
+            created_at: Utc::now(),
+        };
+        log.push(stored.clone());
+        Ok(stored)
+    }
+
+    async fn get_events(&self, room_id: Uuid) -> Result<Vec<StoredEvent>> {
+        Ok(self.state().events.get(&room_id).cloned().unwrap_or_default())
+    }
+
+    async fn get_user_events(&self, user_id: Uuid) -> Result<Vec<StoredEvent>> {
+        let state = self.state();
+        let mut rooms: Vec<_> = state
+            .events
+            .values()
+            .filter_map(|log| {
+                log.iter()
+                    .find(|e| matches!(e.data, GameEvent::Joined { user_id: u, .. } if u == user_id))
+                    .map(|e| (e.created_at, e.room_id, log))
+            })
+            .collect();
+        rooms.sort_by_key(|(joined_at, room_id, _)| (*joined_at, *room_id));
+        Ok(rooms.into_iter().flat_map(|(_, _, log)| log.iter().cloned()).collect())
+    }
+
+    async fn get_active_rooms(&self, since: DateTime<Utc>) -> Result<Vec<Uuid>> {
+        let state = self.state();
+        let mut rooms: Vec<_> = state
+            .events
+            .iter()
+            .filter_map(|(room_id, log)| log.last().map(|e| (e.created_at, *room_id)))
+            .filter(|(at, _)| *at >= since)
+            .collect();
+        rooms.sort();
+        Ok(rooms.into_iter().map(|(_, room_id)| room_id).collect())
+    }
+}
+
+impl MigrationRepo for MemoryDb {
+    //there is no schema to migrate , report everything as applied
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        Ok(MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .map(|m| MigrationStatus {
+                version: m.version,
+                description: m.description.to_string(),
+                applied: true,
+                installed_on: None,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{Result, bail};
+use chrono::{DateTime, Utc};
+use app_config::MigrationMode;
+use serde::{Deserialize, Serialize};
+use sqlx::migrate::Migrator;
+
+use crate::{Db, MigrationRepo};
+
+// All files in db/migrations are compiled into the binary,
+// so a deployed server does not need the sql files or sqlx-cli next to it.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+    pub installed_on: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AppliedMigration {
+    version: i64,
+    installed_on: DateTime<Utc>,
+}
+
+impl MigrationRepo for Db {
+    //every embedded migration and whether the database already has it
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        // the bookkeeping table only exists after the first migration run
+        let table: Option<String> = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations')::text")
+            .fetch_one(&self.pool)
+            .await?;
+
+        let applied = if table.is_some() {
+            sqlx::query_as::<_, AppliedMigration>(
+                r#"
+                SELECT version, installed_on
+                FROM _sqlx_migrations
+                WHERE success
+                "#
+            )
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .map(|m| {
+                let installed_on = applied
+                    .iter()
+                    .find(|a| a.version == m.version)
+                    .map(|a| a.installed_on);
+                MigrationStatus {
+                    version: m.version,
+                    description: m.description.to_string(),
+                    applied: installed_on.is_some(),
+                    installed_on,
+                }
+            })
+            .collect())
+    }
+}
+
+impl Db {
+    pub async fn run_migrations(&self) -> Result<()> {
+        MIGRATOR.run(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn pending_migrations(&self) -> Result<Vec<MigrationStatus>> {
+        Ok(self
+            .migration_status()
+            .await?
+            .into_iter()
+            .filter(|m| !m.applied)
+            .collect())
+    }
+
+    //called from Db::new with the mode picked by the config
+    pub async fn prepare_schema(&self, mode: MigrationMode) -> Result<()> {
+        match mode {
+            MigrationMode::Apply => {
+                self.run_migrations().await?;
+                log::info!("Database migrations applied");
+            }
+            MigrationMode::Check => {
+                let pending = self.pending_migrations().await?;
+                if !pending.is_empty() {
+                    let versions: Vec<String> = pending.iter().map(|m| m.version.to_string()).collect();
+                    bail!(
+                        "database has {} pending migration(s): {} (run the migrate binary first)",
+                        pending.len(),
+                        versions.join(", ")
+                    );
+                }
+            }
+            MigrationMode::Off => {}
+        }
+        Ok(())
+    }
+}
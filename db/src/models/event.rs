@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use anyhow::Result;
+use game::{GameEvent, RoomGame, replay};
+
+use crate::{Db, EventRepo};
+
+// One row of the game_events log.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StoredEvent {
+    pub room_id: Uuid,
+    pub seq: i32,
+
+    #[sqlx(json)]
+    pub data: GameEvent,
+
+    pub created_at: DateTime<Utc>,
+}
+
+// Rebuild a room's game from its stored events , see game::replay.
+pub fn replay_events(events: &[StoredEvent], ply: Option<usize>) -> Result<Box<dyn RoomGame>> {
+    replay(events.iter().map(|e| &e.data), ply).map_err(|e| anyhow::anyhow!("invalid event log: {}", e))
+}
+
+// seq is picked inside the insert , the primary key rejects two writers taking the same one.
+// Takes any executor so apply_move can log its move inside its own transaction.
+pub(crate) async fn insert_event<'e>(executor: impl sqlx::PgExecutor<'e>, room_id: Uuid, event: &GameEvent) -> Result<StoredEvent> {
+    let stored = sqlx::query_as::<_, StoredEvent>(
+        r#"
+        INSERT INTO game_events (room_id, seq, kind, data)
+        SELECT $1, COALESCE(MAX(seq), 0) + 1, $2, $3
+        FROM game_events
+        WHERE room_id = $1
+        RETURNING room_id, seq, data, created_at
+        "#
+    )
+    .bind(room_id)
+    .bind(event.kind())
+    .bind(sqlx::types::Json(event))
+    .fetch_one(executor)
+    .await?;
+
+    Ok(stored)
+}
+
+impl EventRepo for Db {
+    async fn append_event(&self, room_id: Uuid, event: &GameEvent) -> Result<StoredEvent> {
+        insert_event(&self.pool, room_id, event).await
+    }
+
+    async fn get_events(&self, room_id: Uuid) -> Result<Vec<StoredEvent>> {
+        let events = sqlx::query_as::<_, StoredEvent>(
+            r#"
+            SELECT room_id, seq, data, created_at
+            FROM game_events
+            WHERE room_id = $1
+            ORDER BY seq
+            "#
+        )
+        .bind(room_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn get_user_events(&self, user_id: Uuid) -> Result<Vec<StoredEvent>> {
+        let events = sqlx::query_as::<_, StoredEvent>(
+            r#"
+            SELECT e.room_id, e.seq, e.data, e.created_at
+            FROM game_events e
+            JOIN (
+                SELECT room_id, MIN(created_at) AS joined_at
+                FROM game_events
+                WHERE kind = 'joined'
+                  AND data->>'user_id' = $1::text
+                GROUP BY room_id
+            ) joined ON joined.room_id = e.room_id
+            ORDER BY joined.joined_at, e.room_id, e.seq
+            "#
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn get_active_rooms(&self, since: DateTime<Utc>) -> Result<Vec<Uuid>> {
+        let rooms = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT room_id
+            FROM game_events
+            GROUP BY room_id
+            HAVING MAX(created_at) >= $1
+            ORDER BY MAX(created_at)
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rooms)
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Duration, Utc};
+use anyhow::Result;
+
+use crate::{Db, InviteRepo};
+
+//characters used in invite codes , no 0/O or 1/I/L so codes can be read out loud
+const INVITE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+pub const INVITE_CODE_LEN: usize = 6;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RoomInvite {
+    pub code: String,
+
+    pub room_id: Uuid,
+    pub created_by: Uuid,
+
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+//random short code built from the bytes of a v4 uuid
+pub fn new_invite_code() -> String {
+    Uuid::new_v4()
+        .as_bytes()
+        .iter()
+        .take(INVITE_CODE_LEN)
+        .map(|b| INVITE_ALPHABET[*b as usize % INVITE_ALPHABET.len()] as char)
+        .collect()
+}
+
+impl InviteRepo for Db {
+    async fn create_invite(&self, room_id: Uuid, created_by: Uuid, ttl: Duration) -> Result<RoomInvite> {
+        let expires_at = Utc::now() + ttl;
+
+        // codes are short so a collision is possible , just try again with a new one
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let res = sqlx::query_as::<_, RoomInvite>(
+                r#"
+                INSERT INTO room_invites (code, room_id, created_by, expires_at)
+                VALUES ($1, $2, $3, $4)
+                RETURNING *
+                "#
+            )
+            .bind(new_invite_code())
+            .bind(room_id)
+            .bind(created_by)
+            .bind(expires_at)
+            .fetch_one(&self.pool)
+            .await;
+
+            match res {
+                Ok(invite) => return Ok(invite),
+                Err(sqlx::Error::Database(e)) if e.is_unique_violation() && attempts < 5 => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    //only invites that are neither revoked nor expired
+    async fn get_active_invite(&self, code: &str) -> Result<RoomInvite> {
+        let invite = sqlx::query_as::<_, RoomInvite>(
+            r#"
+            SELECT *
+            FROM room_invites
+            WHERE code = $1
+              AND revoked_at IS NULL
+              AND expires_at > NOW()
+            "#
+        )
+        .bind(code.to_uppercase())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| {
+            anyhow::anyhow!("Invite not found or expired: {}", err)
+        })?;
+
+        Ok(invite)
+    }
+
+    async fn revoke_invite(&self, code: &str, user_id: Uuid) -> Result<RoomInvite> {
+        let invite = sqlx::query_as::<_, RoomInvite>(
+            r#"
+            UPDATE room_invites
+            SET revoked_at = NOW()
+            WHERE code = $1
+              AND created_by = $2
+              AND revoked_at IS NULL
+            RETURNING *
+            "#
+        )
+        .bind(code.to_uppercase())
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| {
+            anyhow::anyhow!("Failed to revoke invite: {}", err)
+        })?;
+
+        Ok(invite)
+    }
+}
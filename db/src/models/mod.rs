@@ -2,4 +2,16 @@ pub mod user;
 pub use user::*;
 
 pub mod room;
-pub use room::*;
\ No newline at end of file
+pub use room::*;
+
+pub mod invite;
+pub use invite::*;
+
+pub mod moves;
+pub use moves::*;
+
+pub mod event;
+pub use event::*;
+
+pub mod replay;
+pub use replay::*;
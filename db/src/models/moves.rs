@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use anyhow::{Result, bail};
+use game::{CellMove, ConnectFour, GameEvent, GameKind, GameState, Mark, MovePayload, Qubic, RoomStatus, connect_four::COLUMNS, decode_payload, encode_payload};
+
+use crate::{Db, MoveRepo, VersionConflict, models::{Room, insert_event}};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Move {
+    pub room_id: Uuid,
+    pub ply: i32,
+
+    pub position: i32,
+    pub mark: Mark,
+    pub player_id: Uuid,
+
+    pub created_at: DateTime<Utc>,
+}
+
+impl Move {
+    // The move as the ws server and the event log write it.
+    // Connect four rows keep the cell the disc landed on , the move is its column.
+    pub fn payload(&self, game: GameKind) -> MovePayload {
+        let position = self.position as usize;
+        match game {
+            GameKind::ConnectFour => encode_payload::<ConnectFour>(position % COLUMNS),
+            GameKind::Qubic => encode_payload::<Qubic>(position),
+            _ => encode_payload::<GameState>(CellMove { position, mark: None }),
+        }
+    }
+}
+
+// The log entry for a move apply_move wrote , in the same format the ws server logs moves in.
+pub fn moved_event(room: &Room, mv: &Move) -> GameEvent {
+    GameEvent::Moved {
+        user_id: mv.player_id,
+        mark: mv.mark,
+        mv: mv.payload(room.game),
+    }
+}
+
+// The position MoveRepo::apply_move takes for a move in the ws format ,
+// the cell for tic-tac-toe and qubic , the column for connect four.
+pub fn move_position(game: GameKind, mv: &MovePayload) -> Result<usize> {
+    let position = match game {
+        GameKind::TicTacToe => decode_payload::<GameState>(mv).map(|m| m.position),
+        GameKind::ConnectFour => decode_payload::<ConnectFour>(mv),
+        GameKind::Qubic => decode_payload::<Qubic>(mv),
+        GameKind::Multiplayer => bail!("{} games are played over the websocket", game),
+    };
+    position.map_err(anyhow::Error::msg)
+}
+
+// Result of validating a move against the current room row,
+// everything the storage has to write back.
+#[derive(Debug, Clone)]
+pub struct MovePlan {
+    pub ply: i32,
+    pub position: usize, //cell the mark ended up on , for connect four the column only picks it , qubic counts x fastest then y then z
+    pub mark: Mark,
+    pub board_state: String,
+    pub next_turn: Mark,
+    pub winner: Option<Mark>,
+    pub status: RoomStatus,
+}
+
+// Reject a write made against a stale copy of the room.
+pub fn check_version(room: &Room, expected_version: Option<i32>) -> Result<()> {
+    match expected_version {
+        Some(expected) if expected != room.version => Err(VersionConflict {
+            room_id: room.id,
+            expected,
+            actual: room.version,
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+// Check a move against the room and work out the new room state.
+// Shared by every storage backend so they all enforce the same rules.
+pub fn plan_move(room: &Room, player_id: Uuid, position: usize) -> Result<MovePlan> {
+    if room.status != RoomStatus::Playing {
+        bail!("game is not in progress");
+    }
+
+    let mark = if room.player_x_id == player_id {
+        Mark::X
+    } else if room.player_o_id == Some(player_id) {
+        Mark::O
+    } else {
+        bail!("user not in the room");
+    };
+    if room.next_turn != mark {
+        bail!("its not your turn");
+    }
+
+    //the rules themselves live in the game crate , the same ones the ws server plays by
+    match room.game {
+        GameKind::TicTacToe => {
+            let mut game = room.game_state()?;
+            game.apply_move(position, mark).map_err(anyhow::Error::msg)?;
+            Ok(MovePlan {
+                ply: game.board.iter().filter(|c| c.is_some()).count() as i32,
+                position,
+                mark,
+                board_state: game.cells(),
+                next_turn: game.turn,
+                winner: game.winner,
+                status: game.status,
+            })
+        }
+        GameKind::ConnectFour => {
+            let mut game = room.connect_four()?;
+            let cell = game.drop_disc(position, mark).map_err(anyhow::Error::msg)?;
+            Ok(MovePlan {
+                ply: game.board.iter().flatten().filter(|c| c.is_some()).count() as i32,
+                position: cell,
+                mark,
+                board_state: game.cells(),
+                next_turn: game.turn,
+                winner: game.winner,
+                status: game.status,
+            })
+        }
+        //room rows have two seats , three and four player games only live in the ws server
+        GameKind::Multiplayer => bail!("{} games are played over the websocket", room.game),
+        GameKind::Qubic => {
+            let mut game = room.qubic()?;
+            game.place(position, mark).map_err(anyhow::Error::msg)?;
+            Ok(MovePlan {
+                ply: game.cells().chars().filter(|c| *c != '-').count() as i32,
+                position,
+                mark,
+                board_state: game.cells(),
+                next_turn: game.turn,
+                winner: game.winner,
+                status: game.status,
+            })
+        }
+    }
+}
+
+impl MoveRepo for Db {
+    // the room row is locked for the whole transaction so two moves
+    // for the same room can't both be validated against the old board
+    async fn apply_move(&self, room_id: Uuid, player_id: Uuid, position: usize, expected_version: Option<i32>) -> Result<(Room, Move)> {
+        let mut tx = self.pool.begin().await?;
+
+        let room = sqlx::query_as::<_, Room>(
+            r#"
+            SELECT *
+            FROM rooms
+            WHERE id = $1
+            FOR UPDATE
+            "#
+        )
+        .bind(room_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        check_version(&room, expected_version)?;
+        let plan = plan_move(&room, player_id, position)?;
+
+        let mv = sqlx::query_as::<_, Move>(
+            r#"
+            INSERT INTO moves (room_id, ply, position, mark, player_id)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING room_id, ply, position::INTEGER AS position, mark, player_id, created_at
+            "#
+        )
+        .bind(room_id)
+        .bind(plan.ply)
+        .bind(plan.position as i16)
+        .bind(plan.mark)
+        .bind(player_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let room = sqlx::query_as::<_, Room>(
+            r#"
+            UPDATE rooms
+            SET board_state = $2,
+                next_turn = $3,
+                winner = $4,
+                status = $5,
+                version = version + 1,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(room_id)
+        .bind(&plan.board_state)
+        .bind(plan.next_turn)
+        .bind(plan.winner)
+        .bind(plan.status)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        insert_event(&mut *tx, room_id, &moved_event(&room, &mv)).await?;
+        tx.commit().await?;
+        Ok((room, mv))
+    }
+
+    async fn get_moves(&self, room_id: Uuid) -> Result<Vec<Move>> {
+        let moves = sqlx::query_as::<_, Move>(
+            r#"
+            SELECT room_id, ply, position::INTEGER AS position, mark, player_id, created_at
+            FROM moves
+            WHERE room_id = $1
+            ORDER BY ply
+            "#
+        )
+        .bind(room_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(moves)
+    }
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use anyhow::{Result, anyhow, bail};
+use game::{CellMove, GameEvent, GameKind, GameRecord, GameResult, GameState, Mark, MovePayload, RESIGNATION, Replay, RoomStatus, RuleSet, TEAM_SIZE, TeamPlay, decode_payload};
+
+use crate::models::StoredEvent;
+
+// One ply of a replay , the board is the position after the move.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayMove {
+    pub ply: usize,
+    #[serde(flatten)]
+    pub mv: MovePayload, //the move as the game takes it , `position` for tic-tac-toe , `column` for connect four , `x` `y` `z` for qubic
+    pub mark: Mark,
+    pub user_id: Uuid,
+
+    pub board: serde_json::Value, //the board as the game serializes it
+    pub status: RoomStatus,
+    pub winner: Option<Mark>,
+
+    pub at: DateTime<Utc>,
+    pub think_ms: i64, //time since the previous move (or since the game started)
+}
+
+// A whole game rebuilt from the room's event log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameReplay {
+    pub room_id: Uuid,
+    pub game: GameKind,
+    #[serde(default)]
+    pub rules: RuleSet,
+    pub players: Vec<Uuid>, //in seat order , players[0] played X (team rooms go round the table , see Replay)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub teams: Option<TeamPlay>,
+    pub status: RoomStatus,
+    pub winner: Option<Mark>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resigned: Option<Mark>, //the player whose resignation (or leaving) ended the game
+
+    pub started_at: Option<DateTime<Utc>>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub moves: Vec<ReplayMove>,
+}
+
+impl GameReplay {
+    // Rebuild the latest game in the log (a reused room id starts over at its last `created` event).
+    // None when the room has no events.
+    pub fn from_events(room_id: Uuid, events: &[StoredEvent]) -> Result<Option<Self>> {
+        if events.is_empty() {
+            return Ok(None);
+        }
+
+        let mut state = Replay::new();
+        let mut replay = Self::empty(room_id);
+        let mut last_at = None;
+
+        for stored in events {
+            let event = &stored.data;
+            let was_playing = state.game.is_playing();
+            state
+                .apply(event)
+                .map_err(|e| anyhow!("invalid event log at seq {}: {}", stored.seq, e))?;
+
+            match event {
+                GameEvent::Created { game, rules, teams, .. } => {
+                    replay = Self::empty(room_id);
+                    replay.game = *game;
+                    replay.rules = *rules;
+                    replay.teams = *teams;
+                    last_at = None;
+                }
+                GameEvent::Moved { user_id, mark, mv } => {
+                    let since = last_at.unwrap_or(stored.created_at);
+                    replay.moves.push(ReplayMove {
+                        ply: state.ply,
+                        mv: mv.clone(),
+                        mark: *mark,
+                        user_id: *user_id,
+                        board: state.game.state().get("board").cloned().unwrap_or_default(),
+                        status: state.game.status(),
+                        winner: state.game.winner(),
+                        at: stored.created_at,
+                        think_ms: (stored.created_at - since).num_milliseconds(),
+                    });
+                    last_at = Some(stored.created_at);
+                }
+                _ => {}
+            }
+
+            if !was_playing && state.game.is_playing() {
+                replay.started_at = Some(stored.created_at);
+                last_at = Some(stored.created_at);
+            }
+            if was_playing && state.game.status().is_finished() {
+                replay.ended_at = Some(stored.created_at);
+                if let GameEvent::Resigned { mark, .. } = event {
+                    replay.resigned = Some(*mark);
+                }
+            }
+        }
+
+        replay.players = state.table();
+        replay.status = state.game.status();
+        replay.winner = state.game.winner();
+        Ok(Some(replay))
+    }
+
+    // Everybody who played `seat` , one player unless it was a team game.
+    // Two seats is all the record format knows , other games never get here.
+    pub fn seat_players(&self, seat: usize) -> Vec<Uuid> {
+        let seats = if self.teams.is_some() { self.players.len().div_ceil(TEAM_SIZE).max(1) } else { 2 };
+        self.players.iter().skip(seat).step_by(seats).copied().collect()
+    }
+
+    // Moves of a tic-tac-toe game played one cell at a time on the 3x3 board ,
+    // the record format and the solver only know those (see RuleSet::single_cell_moves).
+    pub fn cell_moves(&self) -> Result<Vec<CellMove>> {
+        if self.game != GameKind::TicTacToe {
+            bail!("{} games have no move list", self.game);
+        }
+        if !self.rules.single_cell_moves() {
+            bail!("{} games have no move list", self.rules);
+        }
+        self.moves
+            .iter()
+            .map(|m| decode_payload::<GameState>(&m.mv).map_err(anyhow::Error::msg))
+            .collect()
+    }
+
+    // The game in the portable text format , `x` and `o` are the names to put in the player tags.
+    // Only 3x3 tic-tac-toe games can be written , see cell_moves.
+    pub fn to_record(&self, x: &str, o: &str) -> Result<GameRecord> {
+        let mut record = GameRecord::new();
+        record.set_tag("Event", "tik-tok-toe");
+        let date = self.started_at.map(|t| t.format("%Y.%m.%d").to_string());
+        record.set_tag("Date", date.unwrap_or_else(|| "????.??.??".to_string()));
+        record.set_tag("X", x);
+        record.set_tag("O", o);
+        record.set_tag("Variant", self.rules.as_str());
+        record.set_tag("TimeControl", "-"); //games have no clock
+        record.set_tag("RoomId", self.room_id.to_string());
+        //the moves of a resigned game don't reach its result , see GameRecord::replay
+        record.set_tag("Termination", if self.resigned.is_some() { RESIGNATION } else { "normal" });
+
+        record.moves = self.cell_moves()?;
+        record.result = GameResult::from_outcome(self.status, self.winner);
+        record.set_tag("Result", record.result.as_str());
+        Ok(record)
+    }
+
+    fn empty(room_id: Uuid) -> Self {
+        Self {
+            room_id,
+            game: GameKind::default(),
+            rules: RuleSet::default(),
+            players: Vec::new(),
+            teams: None,
+            status: RoomStatus::Waiting,
+            winner: None,
+            resigned: None,
+            started_at: None,
+            ended_at: None,
+            moves: Vec::new(),
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
-use anyhow::Result;
+use anyhow::{Result, bail};
+use game::{ConnectFour, GameKind, GameState, Mark, Qubic, RoomGame, RoomStatus, RuleSet};
 
-use crate::Db;
+use crate::{Db, RoomRepo, VersionConflict};
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Room {
     pub id: Uuid,
 
@@ -13,33 +14,118 @@ pub struct Room {
     pub player_o_id: Option<Uuid>,
 
     pub board_state: String,
-    pub next_turn: String,
-    pub winner: Option<String>,
+    pub next_turn: Mark,
+    pub winner: Option<Mark>,
 
-    pub status: String,
+    pub status: RoomStatus,
+    pub game: GameKind, //what board_state holds , see GameKind::empty_cells
+    pub rules: RuleSet, //how moves on the board are scored , see check_rules
+
+    #[serde(skip_serializing)]
+    pub password: Option<String>, //argon2 hash , set for private rooms , never sent to clients
+
+    pub version: i32, //incremented by every update , see VersionConflict
 
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 
-impl Db {
-    pub async fn create_room(&self, player_x_id: Uuid) -> Result<Room> {
+impl Room {
+    // The row as a game position , board_state holds one character per cell.
+    // Display gives the compact notation (see game::notation).
+    pub fn game_state(&self) -> Result<GameState> {
+        if self.game != GameKind::TicTacToe {
+            anyhow::bail!("room {} plays {}", self.id, self.game);
+        }
+        let board = GameState::board_from_cells(&self.board_state).map_err(anyhow::Error::msg)?;
+        Ok(GameState::from_parts(board, self.next_turn, self.status, self.winner).with_rules(self.rules))
+    }
+
+    pub fn connect_four(&self) -> Result<ConnectFour> {
+        if self.game != GameKind::ConnectFour {
+            anyhow::bail!("room {} plays {}", self.id, self.game);
+        }
+        let board = ConnectFour::board_from_cells(&self.board_state).map_err(anyhow::Error::msg)?;
+        Ok(ConnectFour::from_parts(board, self.next_turn, self.status, self.winner))
+    }
+
+    pub fn qubic(&self) -> Result<Qubic> {
+        if self.game != GameKind::Qubic {
+            anyhow::bail!("room {} plays {}", self.id, self.game);
+        }
+        let board = Qubic::board_from_cells(&self.board_state).map_err(anyhow::Error::msg)?;
+        Ok(Qubic::from_parts(board, self.next_turn, self.status, self.winner))
+    }
+
+    // The row as the game the ws server plays , whichever kind it holds.
+    pub fn room_game(&self) -> Result<Box<dyn RoomGame>> {
+        match self.game {
+            GameKind::TicTacToe => Ok(Box::new(self.game_state()?)),
+            GameKind::ConnectFour => Ok(Box::new(self.connect_four()?)),
+            GameKind::Qubic => Ok(Box::new(self.qubic()?)),
+            GameKind::Multiplayer => bail!("{} games are played over the websocket", self.game),
+        }
+    }
+}
+
+// Status of a game that ended with `winner` (None is a draw).
+pub fn finished_status(winner: Option<Mark>) -> RoomStatus {
+    if winner.is_some() { RoomStatus::Won } else { RoomStatus::Draw }
+}
+
+// Rules a room row can be played by.
+// The row only keeps the board and a move is only a position , so rule sets with
+// hidden state or a chosen mark (quantum , fog , wild ...) are played over the websocket.
+// So are games for more than two , a row has an X and an O seat and the game_kind / mark
+// enums in postgres know nothing else.
+pub fn check_rules(game: GameKind, rules: RuleSet) -> Result<()> {
+    if game.new_game(rules, None).map_err(anyhow::Error::msg)?.player_count() > 2 {
+        bail!("{} rooms are created over the websocket", game);
+    }
+    if !rules.single_cell_moves() || rules.chooses_mark() {
+        bail!("{} rooms are played over the websocket", rules);
+    }
+    Ok(())
+}
+
+// Explain why a version-checked join updated nothing.
+// `current` is the room as it is now (None when it doesn't exist).
+pub fn join_error(room_id: Uuid, current: Option<Room>, expected_version: i32) -> anyhow::Error {
+    match current {
+        None => anyhow::anyhow!("Failed to join room: room {} not found", room_id),
+        Some(room) if room.version != expected_version => VersionConflict {
+            room_id,
+            expected: expected_version,
+            actual: room.version,
+        }
+        .into(),
+        Some(_) => anyhow::anyhow!("Failed to join room: room is not open for joining"),
+    }
+}
+
+impl RoomRepo for Db {
+    async fn create_room(&self, player_x_id: Uuid, password: Option<&str>, game: GameKind, rules: RuleSet) -> Result<Room> {
+        check_rules(game, rules)?;
         let room = sqlx::query_as::<_, Room>(
             r#"
-            INSERT INTO rooms (player_x_id)
-            VALUES ($1)
+            INSERT INTO rooms (player_x_id, password, game, rules, board_state)
+            VALUES ($1, $2, $3, $4, $5)
             RETURNING *
             "#
         )
         .bind(player_x_id)
+        .bind(password)
+        .bind(game)
+        .bind(rules)
+        .bind(game.empty_cells())
         .fetch_one(&self.pool)
         .await?;
 
         Ok(room)
     }
 
-    pub async fn get_room_by_room_id(&self, room_id: Uuid) -> Result<Room> {
+    async fn get_room_by_room_id(&self, room_id: Uuid) -> Result<Room> {
         let room = sqlx::query_as::<_, Room>(
             r#"
             SELECT *
@@ -54,27 +140,62 @@ impl Db {
         Ok(room)
     }
  
-    pub async fn join_room(&self, room_id: Uuid, player_o_id: Uuid) -> Result<Room> {
-      let room = sqlx::query_as::<_, Room>(
+    // only succeeds against the version the caller saw (or the one we just read),
+    // losing a race to another joiner is reported as a VersionConflict
+    async fn join_room(&self, room_id: Uuid, player_o_id: Uuid, expected_version: Option<i32>) -> Result<Room> {
+        let expected = match expected_version {
+            Some(v) => v,
+            None => self.get_room_by_room_id(room_id).await?.version,
+        };
+
+        let room = sqlx::query_as::<_, Room>(
             r#"
             UPDATE rooms
             SET player_o_id = $2,
                 status = 'playing',
+                version = version + 1,
                 updated_at = NOW()
             WHERE id = $1
+              AND version = $3
               AND player_o_id IS NULL
               AND status = 'waiting'
             RETURNING *
             "#
-      )
-      .bind(room_id)
-      .bind(player_o_id)
-      .fetch_one(&self.pool)
-      .await
-      .map_err(|err| {
-          anyhow::anyhow!("Failed to join room: {}", err)
-      })?;
-      Ok(room)
+        )
+        .bind(room_id)
+        .bind(player_o_id)
+        .bind(expected)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match room {
+            Some(room) => Ok(room),
+            None => {
+                let current = self.get_room_by_room_id(room_id).await.ok();
+                Err(join_error(room_id, current, expected))
+            }
+        }
+    }
+
+    async fn finish_room(&self, room_id: Uuid, winner: Option<Mark>) -> Result<Room> {
+        let room = sqlx::query_as::<_, Room>(
+            r#"
+            UPDATE rooms
+            SET status = $2,
+                winner = $3,
+                version = version + 1,
+                updated_at = NOW()
+            WHERE id = $1
+              AND status = 'playing'
+            RETURNING *
+            "#
+        )
+        .bind(room_id)
+        .bind(finished_status(winner))
+        .bind(winner)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        room.ok_or_else(|| anyhow::anyhow!("Failed to finish room: game is not in progress"))
     }
-   
 }
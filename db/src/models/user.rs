@@ -1,22 +1,24 @@
-use crate::Db;
+use crate::{Db, UserRepo};
 use anyhow::{Ok, Result};
+use game::{INITIAL_RATING, rate_teams};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize,Clone)]
 pub struct CreateUserResponse {
     pub id : Uuid
 }
 
-#[derive(Serialize,Deserialize)]
+#[derive(Serialize,Deserialize,Clone,sqlx::FromRow)]
 pub struct User{
     pub id :Uuid,
     pub username : String,
-    pub password : String
+    pub password : String,
+    pub rating : i32
 }
 
-impl Db{
+impl UserRepo for Db{
 
-    pub async fn create_user(&self, username: &String, password: &String) -> Result<CreateUserResponse> {
+    async fn create_user(&self, username: &str, password: &str) -> Result<CreateUserResponse> {
         let u = sqlx::query_as!(CreateUserResponse, "INSERT INTO users (username, password) VALUES ($1, $2) RETURNING id", username, password)
             .fetch_one(&self.pool)
             .await?;
@@ -26,13 +28,52 @@ impl Db{
         })
 
     }
-    pub async fn get_user_by_username(&self, username: &String) -> Result<User> {
-        let u = sqlx::query_as!(User, "SELECT id, username, password FROM users WHERE username=$1", username)
+    async fn get_user_by_username(&self, username: &str) -> Result<User> {
+        let u = sqlx::query_as!(User, "SELECT id, username, password, rating FROM users WHERE username=$1", username)
             .fetch_one(&self.pool)
             .await?;
 
         Ok(u)
     }
+    async fn get_user_by_id(&self, id: Uuid) -> Result<User> {
+        let u = sqlx::query_as!(User, "SELECT id, username, password, rating FROM users WHERE id=$1", id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(u)
+    }
+    async fn get_users_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        let users = sqlx::query_as!(User, "SELECT id, username, password, rating FROM users WHERE id = ANY($1)", ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(users)
+    }
+    // the rows are locked until the new ratings are written , two games
+    // finishing at once can't both start from the same old rating
+    async fn update_ratings(&self, seats: &[Vec<Uuid>], places: &[usize]) -> Result<Vec<Vec<i32>>> {
+        let mut tx = self.pool.begin().await?;
 
+        let mut ratings = Vec::with_capacity(seats.len());
+        for seat in seats {
+            let mut team = Vec::with_capacity(seat.len());
+            for id in seat {
+                let rating = sqlx::query_scalar!("SELECT rating FROM users WHERE id=$1 FOR UPDATE", id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                team.push(rating.unwrap_or(INITIAL_RATING));
+            }
+            ratings.push(team);
+        }
 
+        let rated = rate_teams(&ratings, places);
+        for (id, rating) in seats.iter().flatten().zip(rated.iter().flatten()) {
+            sqlx::query!("UPDATE users SET rating=$2, updated_at=NOW() WHERE id=$1", id, rating)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(rated)
+    }
 }
\ No newline at end of file
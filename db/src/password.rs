@@ -0,0 +1,22 @@
+use anyhow::{Result, anyhow};
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use password_hash::{SaltString, rand_core::OsRng};
+
+// Room passwords are stored as argon2 hashes , never as typed.
+// Both calls are slow on purpose , run them off the async threads
+// (web::block in the backend , spawn_blocking in the ws server).
+
+/// Hash a room password for storing , the salt is generated here and kept in the PHC string.
+pub fn hash_password(password: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| anyhow!("failed to hash password: {}", e))
+}
+
+/// Does `password` match a hash from [`hash_password`] , false for anything that isn't a valid hash.
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .is_ok_and(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+}
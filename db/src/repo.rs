@@ -0,0 +1,72 @@
+use std::future::Future;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use game::{GameEvent, GameKind, Mark, RuleSet};
+
+use crate::{MigrationStatus, models::{CreateUserResponse, Move, Room, RoomInvite, StoredEvent, User}};
+
+// Storage operations used by the servers.
+// Db (postgres) and MemoryDb (in-memory, for tests) both implement them,
+// so handlers can be written once against the traits.
+
+pub trait UserRepo {
+    fn create_user(&self, username: &str, password: &str) -> impl Future<Output = Result<CreateUserResponse>> + Send;
+    fn get_user_by_username(&self, username: &str) -> impl Future<Output = Result<User>> + Send;
+    fn get_user_by_id(&self, id: Uuid) -> impl Future<Output = Result<User>> + Send;
+    // every user in `ids` in one query , ids without an account are left out
+    fn get_users_by_ids(&self, ids: &[Uuid]) -> impl Future<Output = Result<Vec<User>>> + Send;
+    // rate a finished game , `seats` holds the players of every seat (more than one in team games)
+    // and lines up with their `places` (0 is first) , see game::rate_teams.
+    // players without an account count as new and nothing is stored for them. Returns the new ratings by seat.
+    fn update_ratings(&self, seats: &[Vec<Uuid>], places: &[usize]) -> impl Future<Output = Result<Vec<Vec<i32>>>> + Send;
+}
+
+pub trait RoomRepo {
+    /// `password` is stored as given , pass it through [`crate::hash_password`] first.
+    fn create_room(&self, player_x_id: Uuid, password: Option<&str>, game: GameKind, rules: RuleSet) -> impl Future<Output = Result<Room>> + Send;
+    fn get_room_by_room_id(&self, room_id: Uuid) -> impl Future<Output = Result<Room>> + Send;
+    /// Fill the O seat , `expected_version` guards against a concurrent update
+    /// (None means whatever version is current when the call starts).
+    fn join_room(&self, room_id: Uuid, player_o_id: Uuid, expected_version: Option<i32>) -> impl Future<Output = Result<Room>> + Send;
+    /// End a game in progress without a move , somebody resigned or walked out.
+    /// `winner` None records a draw.
+    fn finish_room(&self, room_id: Uuid, winner: Option<Mark>) -> impl Future<Output = Result<Room>> + Send;
+}
+
+pub trait InviteRepo {
+    fn create_invite(&self, room_id: Uuid, created_by: Uuid, ttl: Duration) -> impl Future<Output = Result<RoomInvite>> + Send;
+    fn get_active_invite(&self, code: &str) -> impl Future<Output = Result<RoomInvite>> + Send;
+    fn revoke_invite(&self, code: &str, user_id: Uuid) -> impl Future<Output = Result<RoomInvite>> + Send;
+}
+
+pub trait MoveRepo {
+    /// Validate and record a move , returns the updated room and the stored move.
+    /// The `moved` event goes into the room's event log in the same transaction ,
+    /// so the moves table and the log (what replays read) never disagree.
+    /// Fails with a VersionConflict when `expected_version` is no longer current.
+    fn apply_move(&self, room_id: Uuid, player_id: Uuid, position: usize, expected_version: Option<i32>) -> impl Future<Output = Result<(Room, Move)>> + Send;
+    fn get_moves(&self, room_id: Uuid) -> impl Future<Output = Result<Vec<Move>>> + Send;
+}
+
+pub trait EventRepo {
+    /// Append to the room's event log , events are numbered from 1 in the order they are appended.
+    fn append_event(&self, room_id: Uuid, event: &GameEvent) -> impl Future<Output = Result<StoredEvent>> + Send;
+    fn get_events(&self, room_id: Uuid) -> impl Future<Output = Result<Vec<StoredEvent>>> + Send;
+    /// Every event of the rooms the user has joined , in one go: room by room (the one they
+    /// joined first comes first) and in log order inside a room.
+    fn get_user_events(&self, user_id: Uuid) -> impl Future<Output = Result<Vec<StoredEvent>>> + Send;
+    /// Rooms with an event logged at or after `since` , the least recently active first.
+    fn get_active_rooms(&self, since: DateTime<Utc>) -> impl Future<Output = Result<Vec<Uuid>>> + Send;
+}
+
+pub trait MigrationRepo {
+    fn migration_status(&self) -> impl Future<Output = Result<Vec<MigrationStatus>>> + Send;
+}
+
+/// Everything the backend needs from storage.
+pub trait Repo: UserRepo + RoomRepo + InviteRepo + MoveRepo + EventRepo + MigrationRepo + Clone + Send + Sync + 'static {}
+
+impl<T> Repo for T where T: UserRepo + RoomRepo + InviteRepo + MoveRepo + EventRepo + MigrationRepo + Clone + Send + Sync + 'static {}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use game::GameEvent;
+use uuid::Uuid;
+
+use crate::{EventRepo, models::StoredEvent};
+use super::SqliteDb;
+
+// Same as the postgres one , apply_move passes its transaction.
+pub(crate) async fn insert_event<'e>(executor: impl sqlx::SqliteExecutor<'e>, room_id: Uuid, event: &GameEvent) -> Result<StoredEvent> {
+    let stored = sqlx::query_as::<_, StoredEvent>(
+        r#"
+        INSERT INTO game_events (room_id, seq, kind, data, created_at)
+        SELECT $1, COALESCE(MAX(seq), 0) + 1, $2, $3, $4
+        FROM game_events
+        WHERE room_id = $1
+        RETURNING room_id, seq, data, created_at
+        "#
+    )
+    .bind(room_id)
+    .bind(event.kind())
+    .bind(sqlx::types::Json(event))
+    .bind(Utc::now())
+    .fetch_one(executor)
+    .await?;
+
+    Ok(stored)
+}
+
+impl EventRepo for SqliteDb {
+    async fn append_event(&self, room_id: Uuid, event: &GameEvent) -> Result<StoredEvent> {
+        insert_event(&self.pool, room_id, event).await
+    }
+
+    async fn get_events(&self, room_id: Uuid) -> Result<Vec<StoredEvent>> {
+        let events = sqlx::query_as::<_, StoredEvent>(
+            r#"
+            SELECT room_id, seq, data, created_at
+            FROM game_events
+            WHERE room_id = $1
+            ORDER BY seq
+            "#
+        )
+        .bind(room_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn get_user_events(&self, user_id: Uuid) -> Result<Vec<StoredEvent>> {
+        let events = sqlx::query_as::<_, StoredEvent>(
+            r#"
+            SELECT e.room_id, e.seq, e.data, e.created_at
+            FROM game_events e
+            JOIN (
+                SELECT room_id, MIN(created_at) AS joined_at
+                FROM game_events
+                WHERE kind = 'joined'
+                  AND json_extract(data, '$.user_id') = $1
+                GROUP BY room_id
+            ) joined ON joined.room_id = e.room_id
+            ORDER BY joined.joined_at, e.room_id, e.seq
+            "#
+        )
+        .bind(user_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn get_active_rooms(&self, since: DateTime<Utc>) -> Result<Vec<Uuid>> {
+        let rooms = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT room_id
+            FROM game_events
+            GROUP BY room_id
+            HAVING MAX(created_at) >= $1
+            ORDER BY MAX(created_at)
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rooms)
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{InviteRepo, models::{RoomInvite, new_invite_code}};
+use super::SqliteDb;
+
+impl InviteRepo for SqliteDb {
+    async fn create_invite(&self, room_id: Uuid, created_by: Uuid, ttl: Duration) -> Result<RoomInvite> {
+        let now = Utc::now();
+
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let res = sqlx::query_as::<_, RoomInvite>(
+                r#"
+                INSERT INTO room_invites (code, room_id, created_by, expires_at, created_at)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING *
+                "#
+            )
+            .bind(new_invite_code())
+            .bind(room_id)
+            .bind(created_by)
+            .bind(now + ttl)
+            .bind(now)
+            .fetch_one(&self.pool)
+            .await;
+
+            match res {
+                Ok(invite) => return Ok(invite),
+                Err(sqlx::Error::Database(e)) if e.is_unique_violation() && attempts < 5 => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    // timestamps are text in sqlite , so expiry is checked here rather than in sql
+    async fn get_active_invite(&self, code: &str) -> Result<RoomInvite> {
+        let invite = sqlx::query_as::<_, RoomInvite>(
+            r#"
+            SELECT *
+            FROM room_invites
+            WHERE code = $1
+              AND revoked_at IS NULL
+            "#
+        )
+        .bind(code.to_uppercase())
+        .fetch_optional(&self.pool)
+        .await?
+        .filter(|i| i.expires_at > Utc::now())
+        .ok_or_else(|| anyhow::anyhow!("Invite not found or expired"))?;
+
+        Ok(invite)
+    }
+
+    async fn revoke_invite(&self, code: &str, user_id: Uuid) -> Result<RoomInvite> {
+        let invite = sqlx::query_as::<_, RoomInvite>(
+            r#"
+            UPDATE room_invites
+            SET revoked_at = $3
+            WHERE code = $1
+              AND created_by = $2
+              AND revoked_at IS NULL
+            RETURNING *
+            "#
+        )
+        .bind(code.to_uppercase())
+        .bind(user_id)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|err| {
+            anyhow::anyhow!("Failed to revoke invite: {}", err)
+        })?;
+
+        Ok(invite)
+    }
+}
@@ -0,0 +1,110 @@
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
+use app_config::{DatabaseConfig, MigrationMode};
+use sqlx::{
+    SqlitePool,
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+
+use crate::{MigrationRepo, MigrationStatus};
+
+pub mod user;
+pub mod room;
+pub mod invite;
+pub mod moves;
+pub mod event;
+
+// Same schema as db/migrations , rewritten for sqlite types
+// (no gen_random_uuid / TIMESTAMPTZ , ids and timestamps come from rust).
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations_sqlite");
+
+// SQLite storage for local development and small deployments.
+// Selected when DATABASE_URL starts with sqlite: , e.g. sqlite://tictactoe.db
+#[derive(Clone)]
+pub struct SqliteDb {
+    pub pool: SqlitePool,
+}
+
+impl SqliteDb {
+    pub async fn new(config: &DatabaseConfig, migrations: MigrationMode) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(&config.url)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect_with(options)
+            .await?;
+        log::info!("Database connection successful (sqlite)");
+        let db = Self { pool };
+        db.prepare_schema(migrations).await?;
+        Ok(db)
+    }
+
+    pub async fn run_migrations(&self) -> Result<()> {
+        SQLITE_MIGRATOR.run(&self.pool).await?;
+        Ok(())
+    }
+
+    pub async fn prepare_schema(&self, mode: MigrationMode) -> Result<()> {
+        match mode {
+            MigrationMode::Apply => self.run_migrations().await?,
+            MigrationMode::Check => {
+                let pending = self.migration_status().await?.into_iter().filter(|m| !m.applied).count();
+                if pending > 0 {
+                    bail!("database has {} pending migration(s) (run the migrate binary first)", pending);
+                }
+            }
+            MigrationMode::Off => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AppliedMigration {
+    version: i64,
+    installed_on: chrono::DateTime<chrono::Utc>,
+}
+
+impl MigrationRepo for SqliteDb {
+    async fn migration_status(&self) -> Result<Vec<MigrationStatus>> {
+        let table: Option<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let applied = if table.is_some() {
+            sqlx::query_as::<_, AppliedMigration>(
+                r#"
+                SELECT version, installed_on
+                FROM _sqlx_migrations
+                WHERE success
+                "#
+            )
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(SQLITE_MIGRATOR
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .map(|m| {
+                let installed_on = applied
+                    .iter()
+                    .find(|a| a.version == m.version)
+                    .map(|a| a.installed_on);
+                MigrationStatus {
+                    version: m.version,
+                    description: m.description.to_string(),
+                    applied: installed_on.is_some(),
+                    installed_on,
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{MoveRepo, models::{Move, Room, check_version, moved_event, plan_move}};
+use super::{SqliteDb, event::insert_event};
+
+impl MoveRepo for SqliteDb {
+    // sqlite has no row locks , BEGIN IMMEDIATE takes the database write lock
+    // up front which gives the same guarantee as SELECT ... FOR UPDATE
+    async fn apply_move(&self, room_id: Uuid, player_id: Uuid, position: usize, expected_version: Option<i32>) -> Result<(Room, Move)> {
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await?;
+
+        let room = sqlx::query_as::<_, Room>(
+            r#"
+            SELECT *
+            FROM rooms
+            WHERE id = $1
+            "#
+        )
+        .bind(room_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        check_version(&room, expected_version)?;
+        let plan = plan_move(&room, player_id, position)?;
+        let now = Utc::now();
+
+        let mv = sqlx::query_as::<_, Move>(
+            r#"
+            INSERT INTO moves (room_id, ply, position, mark, player_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#
+        )
+        .bind(room_id)
+        .bind(plan.ply)
+        .bind(plan.position as i32)
+        .bind(plan.mark)
+        .bind(player_id)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let room = sqlx::query_as::<_, Room>(
+            r#"
+            UPDATE rooms
+            SET board_state = $2,
+                next_turn = $3,
+                winner = $4,
+                status = $5,
+                version = version + 1,
+                updated_at = $6
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(room_id)
+        .bind(&plan.board_state)
+        .bind(plan.next_turn)
+        .bind(plan.winner)
+        .bind(plan.status)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        insert_event(&mut *tx, room_id, &moved_event(&room, &mv)).await?;
+        tx.commit().await?;
+        Ok((room, mv))
+    }
+
+    async fn get_moves(&self, room_id: Uuid) -> Result<Vec<Move>> {
+        let moves = sqlx::query_as::<_, Move>(
+            r#"
+            SELECT *
+            FROM moves
+            WHERE room_id = $1
+            ORDER BY ply
+            "#
+        )
+        .bind(room_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(moves)
+    }
+}
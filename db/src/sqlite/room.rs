@@ -0,0 +1,106 @@
+use anyhow::Result;
+use game::{GameKind, Mark, RuleSet};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{RoomRepo, models::{Room, check_rules, finished_status, join_error}};
+use super::SqliteDb;
+
+impl RoomRepo for SqliteDb {
+    async fn create_room(&self, player_x_id: Uuid, password: Option<&str>, game: GameKind, rules: RuleSet) -> Result<Room> {
+        check_rules(game, rules)?;
+        let now = Utc::now();
+        let room = sqlx::query_as::<_, Room>(
+            r#"
+            INSERT INTO rooms (id, player_x_id, password, game, rules, board_state, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+            RETURNING *
+            "#
+        )
+        .bind(Uuid::new_v4())
+        .bind(player_x_id)
+        .bind(password)
+        .bind(game)
+        .bind(rules)
+        .bind(game.empty_cells())
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(room)
+    }
+
+    async fn get_room_by_room_id(&self, room_id: Uuid) -> Result<Room> {
+        let room = sqlx::query_as::<_, Room>(
+            r#"
+            SELECT *
+            FROM rooms
+            WHERE id = $1
+            "#
+        )
+        .bind(room_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(room)
+    }
+
+    async fn join_room(&self, room_id: Uuid, player_o_id: Uuid, expected_version: Option<i32>) -> Result<Room> {
+        let expected = match expected_version {
+            Some(v) => v,
+            None => self.get_room_by_room_id(room_id).await?.version,
+        };
+
+        let room = sqlx::query_as::<_, Room>(
+            r#"
+            UPDATE rooms
+            SET player_o_id = $2,
+                status = 'playing',
+                version = version + 1,
+                updated_at = $3
+            WHERE id = $1
+              AND version = $4
+              AND player_o_id IS NULL
+              AND status = 'waiting'
+            RETURNING *
+            "#
+        )
+        .bind(room_id)
+        .bind(player_o_id)
+        .bind(Utc::now())
+        .bind(expected)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match room {
+            Some(room) => Ok(room),
+            None => {
+                let current = self.get_room_by_room_id(room_id).await.ok();
+                Err(join_error(room_id, current, expected))
+            }
+        }
+    }
+
+    async fn finish_room(&self, room_id: Uuid, winner: Option<Mark>) -> Result<Room> {
+        let room = sqlx::query_as::<_, Room>(
+            r#"
+            UPDATE rooms
+            SET status = $2,
+                winner = $3,
+                version = version + 1,
+                updated_at = $4
+            WHERE id = $1
+              AND status = 'playing'
+            RETURNING *
+            "#
+        )
+        .bind(room_id)
+        .bind(finished_status(winner))
+        .bind(winner)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        room.ok_or_else(|| anyhow::anyhow!("Failed to finish room: game is not in progress"))
+    }
+}
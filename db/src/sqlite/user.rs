@@ -0,0 +1,86 @@
+use anyhow::Result;
+use game::{INITIAL_RATING, rate_teams};
+use sqlx::{QueryBuilder, Sqlite};
+use uuid::Uuid;
+
+use crate::{UserRepo, models::{CreateUserResponse, User}};
+use super::SqliteDb;
+
+impl UserRepo for SqliteDb {
+    async fn create_user(&self, username: &str, password: &str) -> Result<CreateUserResponse> {
+        let id = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO users (id, username, password) VALUES ($1, $2, $3) RETURNING id"
+        )
+        .bind(Uuid::new_v4())
+        .bind(username)
+        .bind(password)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(CreateUserResponse { id })
+    }
+
+    async fn get_user_by_username(&self, username: &str) -> Result<User> {
+        let u = sqlx::query_as::<_, User>("SELECT id, username, password, rating FROM users WHERE username=$1")
+            .bind(username)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(u)
+    }
+
+    async fn get_user_by_id(&self, id: Uuid) -> Result<User> {
+        let u = sqlx::query_as::<_, User>("SELECT id, username, password, rating FROM users WHERE id=$1")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(u)
+    }
+
+    async fn get_users_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        //no arrays in sqlite , one placeholder per id
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT id, username, password, rating FROM users WHERE id IN (");
+        let mut list = query.separated(", ");
+        for id in ids {
+            list.push_bind(*id);
+        }
+        list.push_unseparated(")");
+        let users = query.build_query_as::<User>().fetch_all(&self.pool).await?;
+
+        Ok(users)
+    }
+
+    // sqlite has one writer at a time , the transaction keeps the read and the write together
+    async fn update_ratings(&self, seats: &[Vec<Uuid>], places: &[usize]) -> Result<Vec<Vec<i32>>> {
+        let mut tx = self.pool.begin().await?;
+
+        let mut ratings = Vec::with_capacity(seats.len());
+        for seat in seats {
+            let mut team = Vec::with_capacity(seat.len());
+            for id in seat {
+                let rating = sqlx::query_scalar::<_, i32>("SELECT rating FROM users WHERE id=$1")
+                    .bind(id)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+                team.push(rating.unwrap_or(INITIAL_RATING));
+            }
+            ratings.push(team);
+        }
+
+        let rated = rate_teams(&ratings, places);
+        for (id, rating) in seats.iter().flatten().zip(rated.iter().flatten()) {
+            sqlx::query("UPDATE users SET rating=$2, updated_at=CURRENT_TIMESTAMP WHERE id=$1")
+                .bind(id)
+                .bind(rating)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(rated)
+    }
+}
@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Game, GameKind, GameOutcome, Mark, RoomStatus};
+
+pub const COLUMNS: usize = 7;
+pub const ROWS: usize = 6;
+const CONNECT: usize = 4;
+
+/// Connect Four , discs are dropped into a column and fall to the lowest free row.
+/// Four in a row in any direction wins.
+#[derive(Serialize, Debug, Deserialize, Clone)]
+pub struct ConnectFour {
+    pub board: [[Option<Mark>; COLUMNS]; ROWS], //row 0 is the top , discs pile up from row 5
+    pub winner: Option<Mark>,
+    pub status: RoomStatus,
+    pub turn: Mark,
+}
+
+impl Default for ConnectFour {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectFour {
+    pub fn new() -> Self {
+        Self::from_parts([[None; COLUMNS]; ROWS], Mark::X, RoomStatus::Waiting, None)
+    }
+
+    pub fn from_parts(board: [[Option<Mark>; COLUMNS]; ROWS], turn: Mark, status: RoomStatus, winner: Option<Mark>) -> Self {
+        Self { board, winner, status, turn }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.status == RoomStatus::Playing
+    }
+
+    pub fn start(&mut self) {
+        if self.status == RoomStatus::Waiting {
+            self.status = RoomStatus::Playing;
+        }
+    }
+
+    //`mark` gives up , the other side wins
+    pub fn forfeit(&mut self, mark: Mark) -> Result<(), String> {
+        if !self.is_playing() {
+            return Err("game is not in progress".into());
+        }
+        self.status = RoomStatus::Won;
+        self.winner = Some(mark.other());
+        Ok(())
+    }
+
+    //row a disc dropped into `column` would land on , None when the column is full
+    pub fn landing_row(&self, column: usize) -> Option<usize> {
+        (0..ROWS).rev().find(|row| self.board[*row][column].is_none())
+    }
+
+    /// Drop a disc for `mark` into `column`.
+    /// Returns the cell it landed on (row * COLUMNS + column , counted from the top left).
+    pub fn drop_disc(&mut self, column: usize, mark: Mark) -> Result<usize, String> {
+        if !self.is_playing() {
+            return Err("game is not started yet".into());
+        }
+        if mark != self.turn {
+            return Err("its not your turn buddy".into());
+        }
+        if column >= COLUMNS {
+            return Err("Invalid column".into());
+        }
+        let row = self.landing_row(column).ok_or_else(|| "column is full".to_string())?;
+        self.board[row][column] = Some(mark);
+
+        if self.connects(row, column, mark) {
+            self.status = RoomStatus::Won;
+            self.winner = Some(mark);
+        } else if self.board[0].iter().all(|c| c.is_some()) {
+            self.status = RoomStatus::Draw;
+        } else {
+            self.turn = self.turn.other();
+        }
+        Ok(row * COLUMNS + column)
+    }
+
+    //does the disc just placed at (row , column) complete a line
+    fn connects(&self, row: usize, column: usize, mark: Mark) -> bool {
+        const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+        let run = |dr: isize, dc: isize| {
+            (1..CONNECT as isize)
+                .take_while(|i| {
+                    let (r, c) = (row as isize + dr * i, column as isize + dc * i);
+                    (0..ROWS as isize).contains(&r)
+                        && (0..COLUMNS as isize).contains(&c)
+                        && self.board[r as usize][c as usize] == Some(mark)
+                })
+                .count()
+        };
+        DIRECTIONS
+            .iter()
+            .any(|(dr, dc)| 1 + run(*dr, *dc) + run(-dr, -dc) >= CONNECT)
+    }
+
+    //one character per cell row by row from the top , like GameState::cells
+    pub fn cells(&self) -> String {
+        self.board
+            .iter()
+            .flatten()
+            .map(|c| c.map(Mark::as_char).unwrap_or('-'))
+            .collect()
+    }
+
+    pub fn board_from_cells(cells: &str) -> Result<[[Option<Mark>; COLUMNS]; ROWS], String> {
+        if cells.chars().count() != ROWS * COLUMNS {
+            return Err(format!("board needs {} cells: {}", ROWS * COLUMNS, cells));
+        }
+        let mut board = [[None; COLUMNS]; ROWS];
+        for (i, c) in cells.chars().enumerate() {
+            board[i / COLUMNS][i % COLUMNS] = match c {
+                '-' => None,
+                _ => Some(Mark::from_char(c).ok_or_else(|| format!("invalid cell: {}", c))?),
+            };
+        }
+        Ok(board)
+    }
+}
+
+/// Connect Four move payload , `{"column":3}`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ColumnMove {
+    pub column: usize,
+}
+
+impl Game for ConnectFour {
+    const KIND: GameKind = GameKind::ConnectFour;
+
+    type Move = usize;
+    type Payload = ColumnMove;
+
+    fn player_count(&self) -> usize {
+        2
+    }
+
+    fn to_move(&self) -> usize {
+        self.turn.seat()
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if !self.is_playing() {
+            return Vec::new();
+        }
+        (0..COLUMNS).filter(|c| self.board[0][*c].is_none()).collect()
+    }
+
+    fn apply(&mut self, column: usize) -> Result<(), String> {
+        let mark = self.turn;
+        self.drop_disc(column, mark).map(|_| ())
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        match (self.status, self.winner) {
+            (RoomStatus::Won, Some(mark)) => Some(GameOutcome::Win { seat: mark.seat() }),
+            (RoomStatus::Draw, _) => Some(GameOutcome::Draw),
+            _ => None,
+        }
+    }
+
+    fn start(&mut self) {
+        ConnectFour::start(self)
+    }
+
+    fn status(&self) -> RoomStatus {
+        self.status
+    }
+
+    fn forfeit(&mut self, seat: usize) -> Result<(), String> {
+        let mark = Mark::from_seat(seat).ok_or_else(|| format!("no player in seat {}", seat))?;
+        ConnectFour::forfeit(self, mark)
+    }
+
+    fn decode_move(payload: ColumnMove) -> Result<usize, String> {
+        Ok(payload.column)
+    }
+
+    fn encode_move(column: usize) -> ColumnMove {
+        ColumnMove { column }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started()->ConnectFour{
+        let mut game = ConnectFour::new();
+        game.start();
+        game
+    }
+
+    //rows from the top , the game is already running with `turn` to move
+    fn position(rows: [&str; ROWS], turn: Mark)->ConnectFour{
+        let board = ConnectFour::board_from_cells(&rows.concat()).unwrap();
+        ConnectFour::from_parts(board, turn, RoomStatus::Playing, None)
+    }
+
+    fn play(game: &mut ConnectFour, columns: &[usize]){
+        for column in columns {
+            game.apply(*column).unwrap();
+        }
+    }
+
+    #[test]
+    fn discs_fall_to_the_lowest_free_row(){
+        let mut game = started();
+        assert_eq!(game.drop_disc(3, Mark::X), Ok(5 * COLUMNS + 3));
+        assert_eq!(game.drop_disc(3, Mark::O), Ok(4 * COLUMNS + 3));
+        assert_eq!(game.board[5][3], Some(Mark::X));
+        assert_eq!(game.board[4][3], Some(Mark::O));
+        assert_eq!(game.landing_row(3), Some(3));
+        assert_eq!(game.landing_row(0), Some(5));
+    }
+
+    #[test]
+    fn full_columns_are_refused(){
+        let mut game = started();
+        play(&mut game, &[2,2,2,2,2,2]);
+        assert_eq!(game.landing_row(2), None);
+        assert!(!game.legal_moves().contains(&2));
+        assert_eq!(game.drop_disc(2, Mark::X), Err("column is full".into()));
+        assert!(game.drop_disc(COLUMNS, Mark::X).is_err());
+        //nothing changed , X can still play elsewhere
+        assert_eq!(game.turn, Mark::X);
+        assert!(game.is_playing());
+        play(&mut game, &[3]);
+    }
+
+    #[test]
+    fn four_across_or_down_wins(){
+        let mut across = started();
+        play(&mut across, &[0,0,1,1,2,2,3]);
+        assert_eq!(across.status, RoomStatus::Won);
+        assert_eq!(across.outcome(), Some(GameOutcome::Win { seat: 0 }));
+        assert!(across.legal_moves().is_empty());
+
+        let mut down = started();
+        play(&mut down, &[6,0,1,0,1,0,2,0]);
+        assert_eq!(down.winner, Some(Mark::O));
+        assert_eq!(down.outcome(), Some(GameOutcome::Win { seat: 1 }));
+        //three in a row is not enough
+        let mut three = started();
+        play(&mut three, &[0,0,1,1,2,2]);
+        assert!(three.is_playing());
+    }
+
+    #[test]
+    fn four_along_either_diagonal_wins(){
+        let mut rising = position([
+            "-------",
+            "-------",
+            "-------",
+            "--XO---",
+            "-XOO---",
+            "XOOXX--",
+        ], Mark::X);
+        rising.drop_disc(3, Mark::X).unwrap();
+        assert_eq!(rising.winner, Some(Mark::X));
+
+        let mut falling = position([
+            "-------",
+            "-------",
+            "-------",
+            "---OX--",
+            "---OOX-",
+            "--XXOOX",
+        ], Mark::X);
+        falling.drop_disc(3, Mark::X).unwrap();
+        assert_eq!(falling.winner, Some(Mark::X));
+        assert_eq!(falling.status, RoomStatus::Won);
+    }
+
+    #[test]
+    fn a_full_board_without_four_is_a_draw(){
+        let mut game = position([
+            "XX-OXXO",
+            "XXOOXXO",
+            "OOXXOOX",
+            "XXOOXXO",
+            "OOXXOOX",
+            "OOXXOOX",
+        ], Mark::O);
+        assert_eq!(game.legal_moves(), vec![2]);
+        game.drop_disc(2, Mark::O).unwrap();
+        assert_eq!(game.status, RoomStatus::Draw);
+        assert_eq!(game.winner, None);
+        assert_eq!(game.outcome(), Some(GameOutcome::Draw));
+    }
+}
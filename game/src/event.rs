@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{GameKind, GameState, Mark, MovePayload, RoomGame, RoomStatus, RuleSet, TEAM_SIZE, TeamPlay};
+
+/// Something that happened in a room.
+/// The ws server appends these to the `game_events` log in the order they happen
+/// (moves in invite rooms are logged by the db together with the move on the room row),
+/// folding them with [`replay`] rebuilds the game at any point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    //the room was set up , a room id can be reused (invite rooms) so this starts a fresh game
+    Created {
+        private: bool,
+        #[serde(default)]
+        hints: bool,
+        #[serde(default)]
+        game: GameKind, //logs from before connect four only had tic-tac-toe
+        #[serde(default)]
+        rules: RuleSet,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        players: Option<usize>, //seats in a multiplayer room
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        teams: Option<TeamPlay>, //team room , TEAM_SIZE players share every mark
+    },
+    Joined { user_id: Uuid, mark: Mark },
+    Left { user_id: Uuid },
+    Moved {
+        user_id: Uuid,
+        mark: Mark,
+        #[serde(flatten)]
+        mv: MovePayload, //the game's move fields , `position` for tic-tac-toe
+    },
+    Chat { user_id: Uuid, text: String },
+    //gave up , with the resign command or by leaving a game in progress
+    Resigned { user_id: Uuid, mark: Mark },
+}
+
+impl GameEvent {
+    //value of the `kind` column , same as the serde tag
+    pub fn kind(&self) -> &'static str {
+        match self {
+            GameEvent::Created { .. } => "created",
+            GameEvent::Joined { .. } => "joined",
+            GameEvent::Left { .. } => "left",
+            GameEvent::Moved { .. } => "moved",
+            GameEvent::Chat { .. } => "chat",
+            GameEvent::Resigned { .. } => "resigned",
+        }
+    }
+}
+
+/// Room state rebuilt from the event log.
+/// Seats work like the live room: a player sits in the seat of the mark they joined with ,
+/// the game starts once every seat is taken. Leaving frees the seat until the game starts ,
+/// after that a `resigned` event follows and the seat is kept.
+#[derive(Clone)]
+pub struct Replay {
+    pub players: Vec<(Uuid, usize)>, //everybody who sat down and their seat , in the order they joined
+    pub team_size: usize, //players per seat , 1 unless it is a team room
+    pub game: Box<dyn RoomGame>, //the kind named by the last `created` event
+    pub ply: usize,              //moves applied so far
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Replay {
+    pub fn new() -> Self {
+        Self {
+            players: Vec::new(),
+            team_size: 1,
+            game: Box::new(GameState::new()),
+            ply: 0,
+        }
+    }
+
+    pub fn for_game(kind: GameKind, rules: RuleSet, players: Option<usize>, teams: Option<TeamPlay>) -> Result<Self, String> {
+        Ok(Self {
+            team_size: if teams.is_some() { TEAM_SIZE } else { 1 },
+            game: kind.new_game(rules, players)?,
+            ..Self::new()
+        })
+    }
+
+    pub fn apply(&mut self, event: &GameEvent) -> Result<(), String> {
+        match event {
+            GameEvent::Created { game, rules, players, teams, .. } => *self = Self::for_game(*game, *rules, *players, *teams)?,
+            GameEvent::Joined { user_id, mark } => {
+                let seat = mark.seat();
+                if seat >= self.game.player_count() || self.seat_players(seat).len() >= self.team_size {
+                    return Err(format!("seat {} is taken", mark));
+                }
+                self.players.push((*user_id, seat));
+                if self.players.len() == self.game.player_count() * self.team_size {
+                    self.game.start();
+                }
+            }
+            GameEvent::Left { user_id } => {
+                if self.game.status() == RoomStatus::Waiting {
+                    self.players.retain(|(u, _)| u != user_id);
+                }
+            }
+            GameEvent::Moved { mv, mark, .. } => {
+                self.game.play(mark.seat(), mv)?;
+                self.ply += 1;
+            }
+            GameEvent::Chat { .. } => {}
+            GameEvent::Resigned { mark, .. } => self.game.forfeit(mark.seat())?,
+        }
+        Ok(())
+    }
+
+    /// Everybody who sat down in `seat` , in the order they joined.
+    pub fn seat_players(&self, seat: usize) -> Vec<Uuid> {
+        self.players.iter().filter(|(_, s)| *s == seat).map(|(u, _)| *u).collect()
+    }
+
+    /// The players round the table: the first of every seat in seat order , then the second ...
+    /// so player i has seat i % seats (players[0] is X).
+    pub fn table(&self) -> Vec<Uuid> {
+        let seats: Vec<Vec<Uuid>> = (0..self.game.player_count()).map(|s| self.seat_players(s)).collect();
+        let rounds = seats.iter().map(Vec::len).max().unwrap_or(0);
+        (0..rounds).flat_map(|i| seats.iter().filter_map(move |team| team.get(i).copied())).collect()
+    }
+}
+
+/// Fold `events` through the game rules and return the state after `ply` moves
+/// (or after the whole log when `ply` is None).
+/// Events that break the rules are an error , a valid log always replays cleanly.
+pub fn replay<'a>(events: impl IntoIterator<Item = &'a GameEvent>, ply: Option<usize>) -> Result<Box<dyn RoomGame>, String> {
+    let mut state = Replay::new();
+    for event in events {
+        if let GameEvent::Moved { .. } = event
+            && Some(state.ply) == ply
+        {
+            break;
+        }
+        state.apply(event)?;
+    }
+    Ok(state.game)
+}
@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{CellMove, Game, GameKind, GameOutcome, GameState, Mark, RoomStatus, RuleSet};
+
+/// Blind tic-tac-toe , each player only sees their own marks.
+/// Playing into a cell the opponent already holds reveals that mark to the player ,
+/// under `fog` it also costs them the turn , under `fog_retry` they go again.
+/// Everything is revealed once the game is over.
+#[derive(Serialize, Debug, Deserialize, Clone)]
+pub struct FogState {
+    #[serde(flatten)]
+    pub game: GameState, //the real board , rules is fog or fog_retry
+    pub revealed: [Vec<usize>; 2], //by seat , opponent cells that player has bumped into
+    pub bumped: bool,              //the last move hit an occupied cell instead of placing a mark
+}
+
+impl FogState {
+    pub fn new(rules: RuleSet) -> Self {
+        Self {
+            game: GameState::new().with_rules(rules),
+            revealed: [Vec::new(), Vec::new()],
+            bumped: false,
+        }
+    }
+
+    //cells `player` knows are taken , their own marks and the ones they bumped into
+    fn known(&self, cell: usize, player: Mark) -> bool {
+        self.game.board[cell] == Some(player) || self.revealed[player.seat()].contains(&cell)
+    }
+
+    pub fn place(&mut self, position: usize, player: Mark) -> Result<(), String> {
+        if !self.game.is_playing() {
+            return Err("game is not started yet".into());
+        }
+        if player != self.game.turn {
+            return Err("its not your turn buddy".into());
+        }
+        if position >= self.game.board.len() {
+            return Err("Invalid position".into());
+        }
+        if self.known(position, player) {
+            return Err("cell is already accoupied ser choose another".into());
+        }
+
+        self.bumped = self.game.board[position].is_some();
+        if self.bumped {
+            self.revealed[player.seat()].push(position);
+            if self.game.rules != RuleSet::FogRetry {
+                self.game.pass_turn();
+            }
+            return Ok(());
+        }
+        self.game.apply_move(position, player)
+    }
+}
+
+impl Game for FogState {
+    const KIND: GameKind = GameKind::TicTacToe;
+
+    type Move = usize;
+    type Payload = CellMove;
+
+    fn player_count(&self) -> usize {
+        2
+    }
+
+    fn to_move(&self) -> usize {
+        self.game.turn.seat()
+    }
+
+    //every cell the player to move doesn't know is taken , hidden opponent marks included
+    fn legal_moves(&self) -> Vec<usize> {
+        if !self.game.is_playing() {
+            return Vec::new();
+        }
+        let player = self.game.turn;
+        (0..self.game.board.len()).filter(|c| !self.known(*c, player)).collect()
+    }
+
+    fn apply(&mut self, position: usize) -> Result<(), String> {
+        let player = self.game.turn;
+        self.place(position, player)
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        Game::outcome(&self.game)
+    }
+
+    fn start(&mut self) {
+        self.game.start()
+    }
+
+    fn status(&self) -> RoomStatus {
+        self.game.status
+    }
+
+    fn forfeit(&mut self, seat: usize) -> Result<(), String> {
+        Game::forfeit(&mut self.game, seat)
+    }
+
+    fn rules(&self) -> RuleSet {
+        self.game.rules
+    }
+
+    //the fog only lifts when the game is over , spectators see no marks at all
+    fn view(&self, seat: Option<usize>) -> Self {
+        if !self.game.is_playing() {
+            return self.clone();
+        }
+        let player = seat.and_then(Mark::from_seat);
+        let mut board = self.game.board;
+        for (cell, mark) in board.iter_mut().enumerate() {
+            if !player.is_some_and(|p| self.known(cell, p)) {
+                *mark = None;
+            }
+        }
+        let mut revealed = [Vec::new(), Vec::new()];
+        if let Some(seat) = seat.filter(|s| *s < revealed.len()) {
+            revealed[seat] = self.revealed[seat].clone();
+        }
+        Self {
+            game: GameState::from_parts(board, self.game.turn, self.game.status, self.game.winner).with_rules(self.game.rules),
+            revealed,
+            bumped: self.bumped,
+        }
+    }
+
+    fn decode_move(payload: CellMove) -> Result<usize, String> {
+        match payload.mark {
+            Some(_) => Err("fog moves only take a position".into()),
+            None => Ok(payload.position),
+        }
+    }
+
+    fn encode_move(position: usize) -> CellMove {
+        CellMove::new(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(rules: RuleSet) -> FogState {
+        let mut game = FogState::new(rules);
+        game.start();
+        game
+    }
+
+    #[test]
+    fn bumping_into_a_hidden_mark_reveals_it_and_costs_the_turn() {
+        let mut game = started(RuleSet::Fog);
+        game.apply(4).unwrap();
+        //O can't see the X in the middle and tries it
+        assert!(game.legal_moves().contains(&4));
+        game.apply(4).unwrap();
+        assert!(game.bumped);
+        assert_eq!(game.game.board[4], Some(Mark::X));
+        assert_eq!(game.revealed, [vec![], vec![4]]);
+        assert_eq!(game.game.turn, Mark::X);
+
+        //a real move clears the bump , and O won't try the middle again
+        game.apply(0).unwrap();
+        assert!(!game.bumped);
+        assert!(!game.legal_moves().contains(&4));
+        assert!(game.place(4, Mark::O).is_err());
+    }
+
+    #[test]
+    fn fog_retry_lets_the_player_go_again() {
+        let mut game = started(RuleSet::FogRetry);
+        game.apply(4).unwrap();
+        game.apply(4).unwrap();
+        assert!(game.bumped);
+        assert_eq!(game.game.turn, Mark::O);
+        assert_eq!(game.legal_moves(), vec![0, 1, 2, 3, 5, 6, 7, 8]);
+        game.apply(0).unwrap();
+        assert_eq!(game.game.board[0], Some(Mark::O));
+        assert_eq!(game.game.turn, Mark::X);
+    }
+
+    #[test]
+    fn each_seat_only_sees_its_own_marks_and_what_it_bumped_into() {
+        let mut game = started(RuleSet::Fog);
+        for position in [0, 4, 8, 0] {
+            game.apply(position).unwrap(); //O bumps into the X on 0
+        }
+        let x = game.view(Some(0));
+        assert_eq!(x.game.board, [Some(Mark::X), None, None, None, None, None, None, None, Some(Mark::X)]);
+        assert!(x.revealed.iter().all(Vec::is_empty));
+
+        let o = game.view(Some(1));
+        assert_eq!(o.game.board, [Some(Mark::X), None, None, None, Some(Mark::O), None, None, None, None]);
+        assert_eq!(o.revealed, [vec![], vec![0]]);
+
+        //spectators see nothing , and neither does the state sent to them
+        assert_eq!(game.view(None).game.board, [None; 9]);
+        assert_eq!(crate::RoomGame::state_for(&game, None)["board"], serde_json::json!([null, null, null, null, null, null, null, null, null]));
+        assert_eq!(crate::RoomGame::state_for(&game, Some(0))["board"][4], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn the_fog_lifts_when_the_game_ends() {
+        let mut game = started(RuleSet::Fog);
+        for position in [0, 3, 1, 4, 2] {
+            game.apply(position).unwrap();
+        }
+        assert_eq!(game.game.winner, Some(Mark::X));
+        assert_eq!(game.view(Some(1)).game.board, game.game.board);
+        assert_eq!(game.view(None).game.board, game.game.board);
+    }
+}
@@ -0,0 +1,82 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ConnectFour, FogState, GameState, Multiplayer, OrderAndChaos, QuantumState, Qubic, RoomGame, RuleSet, multiplayer};
+
+/// Which game a room plays , picked when the room is created.
+/// Stored as the postgres enum type `game_kind`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "game_kind", rename_all = "snake_case"))]
+pub enum GameKind {
+    #[default]
+    TicTacToe,
+    ConnectFour,
+    Qubic,       //4x4x4 cube
+    Multiplayer, //three or four players on a bigger board
+}
+
+impl GameKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GameKind::TicTacToe => "tic_tac_toe",
+            GameKind::ConnectFour => "connect_four",
+            GameKind::Qubic => "qubic",
+            GameKind::Multiplayer => "multiplayer",
+        }
+    }
+
+    //a fresh game of this kind played by `rules` , waiting for its players.
+    //only multiplayer takes a player count , the rest are always two
+    pub fn new_game(self, rules: RuleSet, players: Option<usize>) -> Result<Box<dyn RoomGame>, String> {
+        if let Some(players) = players
+            && players != 2
+            && self != GameKind::Multiplayer
+        {
+            return Err(format!("{} is for 2 players", self));
+        }
+        match (self, rules) {
+            (GameKind::TicTacToe, RuleSet::OrderAndChaos) => Ok(Box::new(OrderAndChaos::new())),
+            (GameKind::TicTacToe, RuleSet::Quantum) => Ok(Box::new(QuantumState::new())),
+            (GameKind::TicTacToe, RuleSet::Fog | RuleSet::FogRetry) => Ok(Box::new(FogState::new(rules))),
+            (GameKind::TicTacToe, rules) if rules != RuleSet::Elimination => Ok(Box::new(GameState::new().with_rules(rules))),
+            (GameKind::ConnectFour, RuleSet::Standard) => Ok(Box::new(ConnectFour::new())),
+            (GameKind::Qubic, RuleSet::Standard) => Ok(Box::new(Qubic::new())),
+            (GameKind::Multiplayer, rules) => Ok(Box::new(Multiplayer::new(players.unwrap_or(multiplayer::MIN_PLAYERS), rules)?)),
+            (kind, rules) => Err(format!("{} has no {} rules", kind, rules)),
+        }
+    }
+
+    //board_state of a new room row , one character per cell
+    pub fn empty_cells(self) -> String {
+        match self {
+            GameKind::TicTacToe => GameState::new().cells(),
+            GameKind::ConnectFour => ConnectFour::new().cells(),
+            GameKind::Qubic => Qubic::new().cells(),
+            //rooms rows only have two seats , this is never stored
+            GameKind::Multiplayer => "-".repeat(multiplayer::board_size(multiplayer::MIN_PLAYERS).pow(2)),
+        }
+    }
+}
+
+impl fmt::Display for GameKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GameKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tic_tac_toe" => Ok(GameKind::TicTacToe),
+            "connect_four" => Ok(GameKind::ConnectFour),
+            "qubic" => Ok(GameKind::Qubic),
+            "multiplayer" => Ok(GameKind::Multiplayer),
+            _ => Err(format!("invalid game: {}", s)),
+        }
+    }
+}
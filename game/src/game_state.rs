@@ -0,0 +1,181 @@
+use crate::{Mark, RoomStatus, RuleSet, zobrist};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize,Debug,Deserialize,Clone)]
+#[serde(from = "GameStateFields")]
+pub struct GameState {
+    pub board : [Option<Mark>;9],
+    pub winner :Option<Mark>,
+    pub status : RoomStatus,
+    pub turn : Mark,
+    pub rules : RuleSet,
+    #[serde(skip_serializing)]
+    pub hashes : [u64;8] //zobrist hash of board + turn under each of the 8 symmetries (see zobrist::SYMMETRIES) , kept in step by apply_move
+}
+
+//what is sent over the wire , the hash is worked out again on the way in
+#[derive(Deserialize)]
+struct GameStateFields {
+    board : [Option<Mark>;9],
+    winner :Option<Mark>,
+    status : RoomStatus,
+    turn : Mark,
+    #[serde(default)]
+    rules : RuleSet //states saved before rule sets were standard
+}
+
+impl From<GameStateFields> for GameState {
+    fn from(f: GameStateFields) -> Self {
+        Self::from_parts(f.board, f.turn, f.status, f.winner).with_rules(f.rules)
+    }
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState {
+    pub fn new()->Self {
+        Self::from_parts([None;9], Mark::X, RoomStatus::Waiting, None)
+    }
+    pub fn from_parts(board:[Option<Mark>;9],turn:Mark,status:RoomStatus,winner:Option<Mark>)->Self{
+        Self {
+            board,
+            winner,
+            status,
+            turn,
+            rules:RuleSet::Standard,
+            hashes:zobrist::symmetric_hashes(&board, turn)
+        }
+    }
+    //same position played under other rules , set up before the game starts
+    pub fn with_rules(mut self,rules:RuleSet)->Self{
+        self.rules = rules;
+        self
+    }
+    //same for every rotation / mirror of the position , never shared between rule sets.
+    //the smallest of the incremental hashes , the solver's table key
+    pub fn canonical_hash(&self)->u64{
+        self.hashes.iter().min().copied().unwrap_or_default() ^ self.rules.hash_key()
+    }
+    fn switch_turn(&mut self){
+        let keys = zobrist::turn_key(self.turn) ^ zobrist::turn_key(self.turn.other());
+        self.hashes.iter_mut().for_each(|h| *h ^= keys);
+        self.turn = self.turn.other();
+    }
+    pub fn is_playing(&self)->bool{
+        self.status == RoomStatus::Playing
+    }
+    //both seats are taken , the game can start
+    pub fn start(&mut self){
+        if self.status == RoomStatus::Waiting {
+            self.status = RoomStatus::Playing;
+        }
+    }
+    //`mark` gives up (resigned or ran out of time) , the other side wins
+    pub fn forfeit(&mut self,mark:Mark)->Result<(),String>{
+        if !self.is_playing(){
+            return Err("game is not in progress".into());
+        }
+        self.status = RoomStatus::Won;
+        self.winner = Some(mark.other());
+        Ok(())
+    }
+    //the side to move loses its turn without placing anything (a bump in fog of war)
+    pub fn pass_turn(&mut self){
+        self.switch_turn();
+    }
+    pub fn apply_move (&mut self,position:usize,mark:Mark)->Result<(),String>{
+        self.place(position, mark, None)
+    }
+    //`player` moves , `piece` is the mark they want to put down (the rule set decides if they may choose)
+    pub fn place(&mut self,position:usize,player:Mark,piece:Option<Mark>)->Result<(),String>{
+
+        if !self.is_playing(){
+            return Err("game is not started yet".into());
+        }
+        if player != self.turn {
+            return Err("its not your turn buddy".into());
+        }
+        let mark = self.rules.piece(player, piece)?;
+        if position >= 9 {
+            return  Err("Invalid position".into());
+        }
+        if self.board[position].is_some() {
+            return Err("cell is already accoupied ser choose another".into());
+        }
+        self.board[position]= Some(mark);
+        for (hash, images) in self.hashes.iter_mut().zip(zobrist::IMAGES) {
+            *hash ^= zobrist::cell_key(images[position], mark);
+        }
+
+        self.evaluate();
+
+        if self.is_playing(){
+            self.switch_turn();
+        }
+        Ok(())
+
+    }
+    //looks for a finished line , the side to move is the one who just played
+    pub fn evaluate(&mut self){
+        const WINS:[(usize,usize,usize);8] = [
+            (0,1,2),(3,4,5),(6,7,8),
+            (0,3,6),(1,4,7),(2,5,8),
+            (0,4,8),(2,4,6)
+        ];
+        for (a, b, c) in WINS {
+            if let (Some(x), Some(y), Some(z)) = (self.board[a], self.board[b], self.board[c])
+                && x == y && y == z {
+                self.status = RoomStatus::Won;
+                self.winner = Some(self.rules.line_winner(x, self.turn));
+                return;
+            }
+        }
+        if self.board.iter().all(|c|c.is_some()){
+            self.status = RoomStatus::Draw;
+            self.winner = None
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started()->GameState{
+        let mut game = GameState::new();
+        game.start();
+        game
+    }
+
+    #[test]
+    fn hashes_follow_the_moves(){
+        let mut game = started();
+        for position in [4,0,8,2,6] {
+            game.apply_move(position, game.turn).unwrap();
+            assert_eq!(game.hashes, zobrist::symmetric_hashes(&game.board, game.turn));
+        }
+        game.pass_turn();
+        assert_eq!(game.hashes, zobrist::symmetric_hashes(&game.board, game.turn));
+    }
+
+    #[test]
+    fn rotations_and_mirrors_share_a_canonical_hash(){
+        let (mut a, mut b, mut c) = (started(), started(), started());
+        for (x, y, z) in [(0,2,8),(1,5,7)] {
+            a.apply_move(x, a.turn).unwrap();
+            b.apply_move(y, b.turn).unwrap();
+            c.apply_move(z, c.turn).unwrap();
+        }
+        assert_eq!(a.canonical_hash(), b.canonical_hash());
+        assert_eq!(a.canonical_hash(), c.canonical_hash());
+        assert_ne!(a.hashes[0], b.hashes[0]);
+        //same board , other rules
+        let misere = GameState::from_parts(a.board, a.turn, a.status, a.winner).with_rules(RuleSet::Misere);
+        assert_ne!(misere.canonical_hash(), a.canonical_hash());
+    }
+}
@@ -0,0 +1,43 @@
+// Game types shared by the db, backend and ws crates.
+pub mod mark;
+pub use mark::*;
+pub mod room_status;
+pub use room_status::*;
+pub mod game_state;
+pub use game_state::*;
+pub mod event;
+pub use event::*;
+pub mod record;
+pub use record::*;
+pub mod notation;
+pub use notation::*;
+pub mod solver;
+pub use solver::*;
+pub mod review;
+pub use review::*;
+pub mod zobrist;
+pub mod ttable;
+pub use ttable::*;
+pub mod rules;
+pub use rules::*;
+pub mod mcts;
+pub mod game_kind;
+pub use game_kind::*;
+pub mod connect_four;
+pub use connect_four::{ColumnMove, ConnectFour};
+pub mod ruleset;
+pub use ruleset::*;
+pub mod order_and_chaos;
+pub use order_and_chaos::OrderAndChaos;
+pub mod quantum;
+pub use quantum::{QuantumMove, QuantumState};
+pub mod qubic;
+pub use qubic::{CubeMove, Qubic};
+pub mod fog;
+pub use fog::FogState;
+pub mod multiplayer;
+pub use multiplayer::Multiplayer;
+pub mod rating;
+pub use rating::*;
+pub mod team;
+pub use team::*;
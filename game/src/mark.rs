@@ -0,0 +1,80 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// The symbol a player puts on the board.
+/// Stored as the postgres enum type `mark`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "mark"))]
+pub enum Mark {
+    X,
+    O,
+    //third and fourth seats , only multiplayer games use them
+    #[serde(rename = "Δ")]
+    #[cfg_attr(feature = "sqlx", sqlx(rename = "Δ"))]
+    Triangle,
+    #[serde(rename = "□")]
+    #[cfg_attr(feature = "sqlx", sqlx(rename = "□"))]
+    Square,
+}
+
+impl Mark {
+    /// Every mark in seat order.
+    pub const ALL: [Mark; 4] = [Mark::X, Mark::O, Mark::Triangle, Mark::Square];
+
+    //the player who moves after this one in a two player game
+    pub fn other(self) -> Self {
+        self.next(2)
+    }
+
+    //the player who moves after this one when `players` take turns round the table
+    pub fn next(self, players: usize) -> Self {
+        Self::ALL[(self.seat() + 1) % players.clamp(1, Self::ALL.len())]
+    }
+
+    //seat of the player using this mark , X moves first
+    pub fn seat(self) -> usize {
+        match self {
+            Mark::X => 0,
+            Mark::O => 1,
+            Mark::Triangle => 2,
+            Mark::Square => 3,
+        }
+    }
+
+    pub fn from_seat(seat: usize) -> Option<Self> {
+        Self::ALL.get(seat).copied()
+    }
+
+    pub fn as_char(self) -> char {
+        match self {
+            Mark::X => 'X',
+            Mark::O => 'O',
+            Mark::Triangle => 'Δ',
+            Mark::Square => '□',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.as_char() == c)
+    }
+}
+
+impl fmt::Display for Mark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_char())
+    }
+}
+
+impl FromStr for Mark {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next().and_then(Mark::from_char), chars.next()) {
+            (Some(mark), None) => Ok(mark),
+            _ => Err(format!("invalid mark: {}", s)),
+        }
+    }
+}
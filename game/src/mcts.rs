@@ -0,0 +1,402 @@
+use std::{
+    f64::consts::SQRT_2,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Game, GameOutcome, zobrist::splitmix64};
+
+/// How long the search runs and how it explores.
+/// The search stops at whichever limit comes first ,
+/// with neither set it runs `DEFAULT_ITERATIONS`.
+#[derive(Debug, Clone)]
+pub struct MctsConfig {
+    pub iterations: Option<u32>,       //per thread
+    pub time_budget: Option<Duration>, //wall clock , shared by all threads
+    pub threads: usize,                //independent trees searched in parallel , merged at the root
+    pub exploration: f64,              //UCT constant , higher tries weaker moves more often
+    pub seed: Option<u64>,             //fixed seed for repeatable searches
+}
+
+impl MctsConfig {
+    pub const DEFAULT_ITERATIONS: u32 = 10_000;
+    pub const MAX_THREADS: usize = 16;
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: Some(Self::DEFAULT_ITERATIONS),
+            time_budget: None,
+            threads: 1,
+            exploration: SQRT_2,
+            seed: None,
+        }
+    }
+}
+
+/// Statistics of one root move , `value` is the mover's average result (1 win , 0 loss).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MctsMove<M> {
+    #[serde(rename = "move")]
+    pub mv: M,
+    pub visits: u32,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MctsResult<M> {
+    pub best_move: Option<M>, //the most visited move
+    pub value: f64,           //expected result of the best move for the player to move
+    pub iterations: u32,      //playouts over all threads
+    pub moves: Vec<MctsMove<M>>, //most visited first
+}
+
+impl<M> MctsResult<M> {
+    //the same result with every move converted , e.g. down to the field a response shows
+    pub fn map<N>(self, f: impl Fn(M) -> N) -> MctsResult<N> {
+        MctsResult {
+            best_move: self.best_move.map(&f),
+            value: self.value,
+            iterations: self.iterations,
+            moves: self
+                .moves
+                .into_iter()
+                .map(|m| MctsMove { mv: f(m.mv), visits: m.visits, value: m.value })
+                .collect(),
+        }
+    }
+}
+
+/// Monte Carlo tree search with UCT selection and random playouts.
+/// Needs nothing but the rules , so it plays games far too big to solve.
+pub fn search<G>(game: &G, config: &MctsConfig) -> MctsResult<G::Move>
+where
+    G: Game + Sync,
+    G::Move: Send,
+{
+    let deadline = config.time_budget.map(|t| Instant::now() + t);
+    let iterations = match (config.iterations, deadline) {
+        (None, None) => Some(MctsConfig::DEFAULT_ITERATIONS),
+        (limit, _) => limit,
+    };
+    let seed = config.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default()
+    });
+    let threads = config.threads.clamp(1, MctsConfig::MAX_THREADS);
+
+    let run = |i: usize| {
+        let mut tree = Tree::new(game, Rng::new(seed ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)));
+        tree.run(config.exploration, iterations, deadline);
+        tree.root_stats()
+    };
+    let roots: Vec<(u32, Vec<MctsMove<G::Move>>)> = if threads == 1 {
+        vec![run(0)]
+    } else {
+        let run = &run;
+        thread::scope(|s| {
+            let handles: Vec<_> = (0..threads).map(|i| s.spawn(move || run(i))).collect();
+            handles.into_iter().filter_map(|h| h.join().ok()).collect()
+        })
+    };
+
+    //root parallel search , the trees only meet here where their root moves are summed
+    let mut total = 0;
+    let mut moves: Vec<MctsMove<G::Move>> = Vec::new();
+    for (playouts, stats) in roots {
+        total += playouts;
+        for stat in stats {
+            match moves.iter_mut().find(|m| m.mv == stat.mv) {
+                Some(m) => {
+                    let visits = m.visits + stat.visits;
+                    if visits > 0 {
+                        m.value = (m.value * m.visits as f64 + stat.value * stat.visits as f64) / visits as f64;
+                    }
+                    m.visits = visits;
+                }
+                None => moves.push(stat),
+            }
+        }
+    }
+    moves.sort_by(|a, b| b.visits.cmp(&a.visits).then(b.value.total_cmp(&a.value)));
+
+    MctsResult {
+        best_move: moves.first().map(|m| m.mv),
+        value: moves.first().map(|m| m.value).unwrap_or(0.5),
+        iterations: total,
+        moves,
+    }
+}
+
+struct Node<M> {
+    mv: Option<M>, //None at the root
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<M>,
+    mover: usize, //seat that played `mv` , rewards are counted for them
+    visits: u32,
+    reward: f64,
+}
+
+struct Tree<'a, G: Game> {
+    root: &'a G,
+    nodes: Vec<Node<G::Move>>,
+    rng: Rng,
+    playouts: u32,
+}
+
+impl<'a, G: Game> Tree<'a, G> {
+    fn new(root: &'a G, rng: Rng) -> Self {
+        let node = Node {
+            mv: None,
+            parent: None,
+            children: Vec::new(),
+            untried: root.legal_moves(),
+            mover: root.to_move(),
+            visits: 0,
+            reward: 0.0,
+        };
+        Self {
+            root,
+            nodes: vec![node],
+            rng,
+            playouts: 0,
+        }
+    }
+
+    fn run(&mut self, exploration: f64, iterations: Option<u32>, deadline: Option<Instant>) {
+        if self.nodes[0].untried.is_empty() {
+            return; //game over or not started , nothing to choose between
+        }
+        loop {
+            if iterations.is_some_and(|n| self.playouts >= n) || deadline.is_some_and(|d| Instant::now() >= d) {
+                break;
+            }
+            self.iterate(exploration);
+            self.playouts += 1;
+        }
+    }
+
+    fn iterate(&mut self, exploration: f64) {
+        let mut state = self.root.clone();
+        let mut node = 0;
+
+        //selection , walk down fully expanded nodes by UCT
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select(node, exploration);
+            //every move in the tree came from legal_moves , so it always applies
+            let _ = state.apply(self.nodes[node].mv.expect("only the root has no move"));
+        }
+
+        //expansion , add one untried move
+        if !self.nodes[node].untried.is_empty() {
+            let i = self.rng.below(self.nodes[node].untried.len());
+            let mv = self.nodes[node].untried.swap_remove(i);
+            let mover = state.to_move();
+            let _ = state.apply(mv);
+            let untried = if state.outcome().is_some() { Vec::new() } else { state.legal_moves() };
+            self.nodes.push(Node {
+                mv: Some(mv),
+                parent: Some(node),
+                children: Vec::new(),
+                untried,
+                mover,
+                visits: 0,
+                reward: 0.0,
+            });
+            let child = self.nodes.len() - 1;
+            self.nodes[node].children.push(child);
+            node = child;
+        }
+
+        //simulation , random moves to the end
+        while state.outcome().is_none() {
+            let moves = state.legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            let mv = moves[self.rng.below(moves.len())];
+            let _ = state.apply(mv);
+        }
+
+        //backpropagation
+        let outcome = state.outcome();
+        let players = state.player_count().max(1);
+        let mut current = Some(node);
+        while let Some(n) = current {
+            let node = &mut self.nodes[n];
+            node.visits += 1;
+            node.reward += reward(outcome, node.mover, players);
+            current = node.parent;
+        }
+    }
+
+    fn select(&self, node: usize, exploration: f64) -> usize {
+        let log_visits = (self.nodes[node].visits.max(1) as f64).ln();
+        let uct = |c: usize| {
+            let child = &self.nodes[c];
+            let visits = child.visits.max(1) as f64;
+            child.reward / visits + exploration * (log_visits / visits).sqrt()
+        };
+        self.nodes[node]
+            .children
+            .iter()
+            .copied()
+            .max_by(|a, b| uct(*a).total_cmp(&uct(*b)))
+            .unwrap_or(node)
+    }
+
+    fn root_stats(&self) -> (u32, Vec<MctsMove<G::Move>>) {
+        let moves = self.nodes[0]
+            .children
+            .iter()
+            .filter_map(|c| {
+                let child = &self.nodes[*c];
+                Some(MctsMove {
+                    mv: child.mv?,
+                    visits: child.visits,
+                    value: if child.visits == 0 { 0.0 } else { child.reward / child.visits as f64 },
+                })
+            })
+            .collect();
+        (self.playouts, moves)
+    }
+}
+
+//what a finished playout is worth to `seat` , a draw (or a stuck game) is shared evenly
+fn reward(outcome: Option<GameOutcome>, seat: usize, players: usize) -> f64 {
+    match outcome {
+        Some(GameOutcome::Win { seat: winner }) if winner == seat => 1.0,
+        Some(GameOutcome::Win { .. }) => 0.0,
+        Some(GameOutcome::Draw) | None => 1.0 / players as f64,
+    }
+}
+
+//small splitmix generator , playouts only need speed and a fair spread
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        let (state, value) = splitmix64(self.0);
+        self.0 = state;
+        value
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameKind, RoomStatus};
+
+    const SIZE: usize = 15;
+
+    //15x15 gomoku , five in a row wins. Only here to give the search a board far too big to solve
+    #[derive(Clone)]
+    struct Gomoku {
+        board: Vec<Option<usize>>,
+        turn: usize,
+        winner: Option<usize>,
+        placed: usize,
+    }
+
+    impl Gomoku {
+        fn new(x: &[usize], o: &[usize]) -> Self {
+            let mut board = vec![None; SIZE * SIZE];
+            x.iter().for_each(|c| board[*c] = Some(0));
+            o.iter().for_each(|c| board[*c] = Some(1));
+            Self { board, turn: 0, winner: None, placed: x.len() + o.len() }
+        }
+
+        fn five(&self, cell: usize) -> bool {
+            let (row, col) = ((cell / SIZE) as isize, (cell % SIZE) as isize);
+            let run = |dr: isize, dc: isize| {
+                (1..5)
+                    .take_while(|i| {
+                        let (r, c) = (row + dr * i, col + dc * i);
+                        (0..SIZE as isize).contains(&r)
+                            && (0..SIZE as isize).contains(&c)
+                            && self.board[(r * SIZE as isize + c) as usize] == self.board[cell]
+                    })
+                    .count()
+            };
+            [(0, 1), (1, 0), (1, 1), (1, -1)].iter().any(|(dr, dc)| 1 + run(*dr, *dc) + run(-dr, -dc) >= 5)
+        }
+    }
+
+    impl Game for Gomoku {
+        const KIND: GameKind = GameKind::TicTacToe; //never hosted , the search doesn't look at it
+        type Move = usize;
+        type Payload = usize;
+
+        fn player_count(&self) -> usize {
+            2
+        }
+        fn to_move(&self) -> usize {
+            self.turn
+        }
+        fn legal_moves(&self) -> Vec<usize> {
+            if self.outcome().is_some() {
+                return Vec::new();
+            }
+            (0..SIZE * SIZE).filter(|c| self.board[*c].is_none()).collect()
+        }
+        fn apply(&mut self, mv: usize) -> Result<(), String> {
+            if self.board[mv].is_some() {
+                return Err("cell is taken".into());
+            }
+            self.board[mv] = Some(self.turn);
+            self.placed += 1;
+            if self.five(mv) {
+                self.winner = Some(self.turn);
+            }
+            self.turn = 1 - self.turn;
+            Ok(())
+        }
+        fn outcome(&self) -> Option<GameOutcome> {
+            match self.winner {
+                Some(seat) => Some(GameOutcome::Win { seat }),
+                None if self.placed == SIZE * SIZE => Some(GameOutcome::Draw),
+                None => None,
+            }
+        }
+        fn start(&mut self) {}
+        fn status(&self) -> RoomStatus {
+            RoomStatus::Playing
+        }
+        fn forfeit(&mut self, seat: usize) -> Result<(), String> {
+            self.winner = Some(1 - seat);
+            Ok(())
+        }
+        fn decode_move(payload: usize) -> Result<usize, String> {
+            Ok(payload)
+        }
+        fn encode_move(mv: usize) -> usize {
+            mv
+        }
+    }
+
+    #[test]
+    fn finds_the_winning_move_on_a_15x15_board() {
+        //X has four in row 7 , blocked on the left , O has four in column 0 and wins next unless X finishes first
+        let x = [7 * SIZE + 3, 7 * SIZE + 4, 7 * SIZE + 5, 7 * SIZE + 6];
+        let o = [7 * SIZE + 2, 0, SIZE, 2 * SIZE, 3 * SIZE];
+        //200 odd root moves , explore less or the playouts are spread over all of them
+        let config = MctsConfig { iterations: Some(1_500), exploration: 0.5, seed: Some(7), ..Default::default() };
+        let result = search(&Gomoku::new(&x, &o), &config);
+        assert_eq!(result.best_move, Some(7 * SIZE + 7));
+        assert!(result.value > 0.9);
+    }
+}
@@ -0,0 +1,275 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{CellMove, Game, GameKind, GameOutcome, Mark, RoomStatus, RuleSet};
+
+pub const MIN_PLAYERS: usize = 3;
+pub const MAX_PLAYERS: usize = 4;
+/// Marks in a row that make a line , on every board size.
+pub const LINE: usize = 4;
+
+//one more row and column per player keeps the board from filling up before anybody gets a chance
+pub fn board_size(players: usize) -> usize {
+    players + 3
+}
+
+/// Tic-tac-toe for three or four players on a bigger board (6x6 , 7x7) , four in a row makes a line.
+/// Seats take turns round the table X , O , Δ , □.
+/// Standard rules: the first line wins. Elimination: completing a line knocks you out ,
+/// your marks stay on the board and the last player left wins.
+#[derive(Serialize, Debug, Deserialize, Clone)]
+pub struct Multiplayer {
+    pub size: usize,                //the board is size x size
+    pub players: usize,
+    pub board: Vec<Option<Mark>>,   //row by row , position = row * size + column
+    pub out: Vec<Mark>,             //players knocked out (or who gave up) , in the order it happened
+    pub winner: Option<Mark>,
+    pub status: RoomStatus,
+    pub turn: Mark,
+    pub rules: RuleSet,
+}
+
+impl Multiplayer {
+    pub fn new(players: usize, rules: RuleSet) -> Result<Self, String> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&players) {
+            return Err(format!("multiplayer games are for {} to {} players", MIN_PLAYERS, MAX_PLAYERS));
+        }
+        if !matches!(rules, RuleSet::Standard | RuleSet::Elimination) {
+            return Err(format!("multiplayer has no {} rules", rules));
+        }
+        let size = board_size(players);
+        Ok(Self {
+            size,
+            players,
+            board: vec![None; size * size],
+            out: Vec::new(),
+            winner: None,
+            status: RoomStatus::Waiting,
+            turn: Mark::X,
+            rules,
+        })
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.status == RoomStatus::Playing
+    }
+
+    pub fn start(&mut self) {
+        if self.status == RoomStatus::Waiting {
+            self.status = RoomStatus::Playing;
+        }
+    }
+
+    //players still in the game , in seat order
+    pub fn remaining(&self) -> Vec<Mark> {
+        Mark::ALL[..self.players]
+            .iter()
+            .copied()
+            .filter(|m| !self.out.contains(m))
+            .collect()
+    }
+
+    //`mark` gives up and is out. Under elimination the others play on ,
+    //otherwise the game ends there , the players still in share first place and `mark` is last
+    pub fn forfeit(&mut self, mark: Mark) -> Result<(), String> {
+        if !self.is_playing() {
+            return Err("game is not in progress".into());
+        }
+        if mark.seat() >= self.players || self.out.contains(&mark) {
+            return Err(format!("{} is not in the game", mark));
+        }
+        self.knock_out(mark);
+        if self.is_playing() && self.rules != RuleSet::Elimination {
+            self.status = RoomStatus::Draw;
+        }
+        if self.is_playing() && self.turn == mark {
+            self.pass_turn();
+        }
+        Ok(())
+    }
+
+    pub fn place(&mut self, position: usize, player: Mark) -> Result<(), String> {
+        if !self.is_playing() {
+            return Err("game is not started yet".into());
+        }
+        if player != self.turn {
+            return Err("its not your turn buddy".into());
+        }
+        if position >= self.board.len() {
+            return Err("Invalid position".into());
+        }
+        if self.board[position].is_some() {
+            return Err("cell is already accoupied ser choose another".into());
+        }
+        self.board[position] = Some(player);
+
+        if self.makes_line(position) {
+            match self.rules {
+                RuleSet::Elimination => self.knock_out(player),
+                _ => {
+                    self.status = RoomStatus::Won;
+                    self.winner = Some(player);
+                }
+            }
+        }
+        if self.is_playing() && self.board.iter().all(|c| c.is_some()) {
+            self.status = RoomStatus::Draw; //the players still in share it
+        }
+        if self.is_playing() {
+            self.pass_turn();
+        }
+        Ok(())
+    }
+
+    fn knock_out(&mut self, mark: Mark) {
+        self.out.push(mark);
+        if let [last] = self.remaining()[..] {
+            self.status = RoomStatus::Won;
+            self.winner = Some(last);
+        }
+    }
+
+    //round the table to the next player still in
+    fn pass_turn(&mut self) {
+        let mut next = self.turn.next(self.players);
+        while self.out.contains(&next) && next != self.turn {
+            next = next.next(self.players);
+        }
+        self.turn = next;
+    }
+
+    //does the mark on `position` sit in LINE or more of the same in a row
+    fn makes_line(&self, position: usize) -> bool {
+        let Some(mark) = self.board[position] else {
+            return false;
+        };
+        let size = self.size as isize;
+        let (row, col) = ((position / self.size) as isize, (position % self.size) as isize);
+        let same = |r: isize, c: isize| {
+            (0..size).contains(&r) && (0..size).contains(&c) && self.board[(r * size + c) as usize] == Some(mark)
+        };
+        [(0, 1), (1, 0), (1, 1), (1, -1)].iter().any(|(dr, dc)| {
+            let mut count = 1;
+            for sign in [1, -1] {
+                let (mut r, mut c) = (row + dr * sign, col + dc * sign);
+                while same(r, c) {
+                    count += 1;
+                    r += dr * sign;
+                    c += dc * sign;
+                }
+            }
+            count >= LINE
+        })
+    }
+}
+
+impl Game for Multiplayer {
+    const KIND: GameKind = GameKind::Multiplayer;
+
+    type Move = usize;
+    type Payload = CellMove;
+
+    fn player_count(&self) -> usize {
+        self.players
+    }
+
+    fn to_move(&self) -> usize {
+        self.turn.seat()
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if !self.is_playing() {
+            return Vec::new();
+        }
+        (0..self.board.len()).filter(|c| self.board[*c].is_none()).collect()
+    }
+
+    fn apply(&mut self, position: usize) -> Result<(), String> {
+        let player = self.turn;
+        self.place(position, player)
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        match (self.status, self.winner) {
+            (RoomStatus::Won, Some(mark)) => Some(GameOutcome::Win { seat: mark.seat() }),
+            (RoomStatus::Draw, _) => Some(GameOutcome::Draw),
+            _ => None,
+        }
+    }
+
+    //the winner first , then whoever was still in , then the players who went out , the last one out placing best
+    fn places(&self) -> Option<Vec<usize>> {
+        self.outcome()?;
+        let mut places = vec![0; self.players];
+        let mut better = 0;
+        if let Some(winner) = self.winner {
+            better += 1;
+            for mark in self.remaining().into_iter().filter(|m| *m != winner) {
+                places[mark.seat()] = better;
+            }
+        }
+        better += self.remaining().len() - self.winner.map_or(0, |_| 1);
+        for mark in self.out.iter().rev() {
+            places[mark.seat()] = better;
+            better += 1;
+        }
+        Some(places)
+    }
+
+    fn start(&mut self) {
+        Multiplayer::start(self)
+    }
+
+    fn status(&self) -> RoomStatus {
+        self.status
+    }
+
+    fn forfeit(&mut self, seat: usize) -> Result<(), String> {
+        let mark = Mark::from_seat(seat).ok_or_else(|| format!("no player in seat {}", seat))?;
+        Multiplayer::forfeit(self, mark)
+    }
+
+    fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    fn decode_move(payload: CellMove) -> Result<usize, String> {
+        match payload.mark {
+            Some(_) => Err("multiplayer moves only take a position".into()),
+            None => Ok(payload.position),
+        }
+    }
+
+    fn encode_move(position: usize) -> CellMove {
+        CellMove::new(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(players: usize, rules: RuleSet) -> Multiplayer {
+        let mut game = Multiplayer::new(players, rules).unwrap();
+        game.start();
+        game
+    }
+
+    #[test]
+    fn forfeit_ends_a_standard_game_with_the_leaver_last() {
+        let mut game = started(4, RuleSet::Standard);
+        game.forfeit(Mark::O).unwrap();
+        assert_eq!(game.status, RoomStatus::Draw);
+        assert_eq!(Game::places(&game), Some(vec![0, 3, 0, 0]));
+    }
+
+    #[test]
+    fn forfeit_under_elimination_plays_on() {
+        let mut game = started(3, RuleSet::Elimination);
+        game.forfeit(Mark::X).unwrap();
+        assert!(game.is_playing());
+        assert_eq!(game.turn, Mark::O);
+        game.forfeit(Mark::Triangle).unwrap();
+        assert_eq!(game.winner, Some(Mark::O));
+        assert_eq!(Game::places(&game), Some(vec![2, 0, 1]));
+    }
+}
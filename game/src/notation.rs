@@ -0,0 +1,260 @@
+use std::{fmt, str::FromStr};
+
+use crate::{GameState, Mark, RoomStatus, RuleSet};
+
+/// Board shape and winning line length written in the last field , the only one so far.
+pub const STANDARD_PARAMS: &str = "3x3k3";
+
+const SIZE: usize = 3;
+
+// Compact position notation (like chess FEN), four fields separated by spaces:
+//
+//   X1O/3/3 O playing 3x3k3
+//
+// 1. the board , rows from top to bottom (board index 0 first) split by '/',
+//    X and O for marks and a digit for a run of empty cells
+// 2. side to move
+// 3. status: waiting , playing , draw , or won-X / won-O
+//    (the winner is written because a resigned game has no line on the board)
+// 4. variant parameters , width x height and how many in a row win ,
+//    followed by the rule set unless it is standard (3x3k3-misere , 3x3k3-wild , 3x3k3-notakto).
+//    Only rule sets whose moves are single squares fit , see RuleSet::single_cell_moves
+//
+// '_' is accepted instead of spaces so a position can sit in a url path.
+
+impl fmt::Display for GameState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (r, row) in self.board.chunks(SIZE).enumerate() {
+            if r > 0 {
+                f.write_str("/")?;
+            }
+            let mut empty = 0;
+            for cell in row {
+                match cell {
+                    Some(mark) => {
+                        if empty > 0 {
+                            write!(f, "{}", empty)?;
+                            empty = 0;
+                        }
+                        write!(f, "{}", mark)?;
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                write!(f, "{}", empty)?;
+            }
+        }
+        match (self.status, self.winner) {
+            (RoomStatus::Won, Some(winner)) => write!(f, " {} won-{} ", self.turn, winner)?,
+            (status, _) => write!(f, " {} {} ", self.turn, status)?,
+        }
+        match self.rules {
+            RuleSet::Standard => f.write_str(STANDARD_PARAMS),
+            rules => write!(f, "{}-{}", STANDARD_PARAMS, rules),
+        }
+    }
+}
+
+//the rule set after the board shape , standard when there is none
+fn parse_params(s: &str) -> Result<RuleSet, String> {
+    let (shape, rules) = match s.split_once('-') {
+        Some((shape, rules)) => (shape, rules.parse::<RuleSet>().ok()),
+        None => (s, Some(RuleSet::Standard)),
+    };
+    match rules {
+        Some(rules) if shape == STANDARD_PARAMS && rules.single_cell_moves() => Ok(rules),
+        _ => Err(format!("unsupported variant: {}", s)),
+    }
+}
+
+//the 3x3 board is played by X and O only , the multiplayer marks are turned down here
+fn board_mark(c: char) -> Result<Mark, String> {
+    match Mark::from_char(c) {
+        Some(mark @ (Mark::X | Mark::O)) => Ok(mark),
+        _ => Err(format!("invalid cell: {}", c)),
+    }
+}
+
+fn player_mark(s: &str) -> Result<Mark, String> {
+    match s.parse::<Mark>()? {
+        mark @ (Mark::X | Mark::O) => Ok(mark),
+        _ => Err(format!("invalid mark: {}", s)),
+    }
+}
+
+fn parse_board(s: &str) -> Result<[Option<Mark>; 9], String> {
+    let rows: Vec<&str> = s.split('/').collect();
+    if rows.len() != SIZE {
+        return Err(format!("board needs {} rows: {}", SIZE, s));
+    }
+
+    let mut board = [None; 9];
+    for (r, row) in rows.iter().enumerate() {
+        let mut col = 0;
+        for c in row.chars() {
+            match c {
+                '1'..='9' => col += c as usize - '0' as usize,
+                _ => {
+                    let mark = board_mark(c)?;
+                    if col < SIZE {
+                        board[r * SIZE + col] = Some(mark);
+                    }
+                    col += 1;
+                }
+            }
+            if col > SIZE {
+                return Err(format!("row {} is too long: {}", r + 1, row));
+            }
+        }
+        if col != SIZE {
+            return Err(format!("row {} is too short: {}", r + 1, row));
+        }
+    }
+    Ok(board)
+}
+
+impl FromStr for GameState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split([' ', '_']).filter(|f| !f.is_empty()).collect();
+        let [board, turn, status, params] = fields[..] else {
+            return Err(format!("expected 4 fields: {}", s));
+        };
+        let rules = parse_params(params)?;
+        let board = parse_board(board)?;
+        let turn = player_mark(turn)?;
+        let (status, winner) = match status.strip_prefix("won-") {
+            Some(winner) => (RoomStatus::Won, Some(player_mark(winner)?)),
+            None => (status.parse::<RoomStatus>()?, None),
+        };
+        if status == RoomStatus::Won && winner.is_none() {
+            return Err("won needs a winner , write won-X or won-O".into());
+        }
+
+        let xs = board.iter().filter(|c| **c == Some(Mark::X)).count();
+        let os = board.iter().filter(|c| **c == Some(Mark::O)).count();
+        match rules {
+            //both players only place X
+            RuleSet::Notakto if os > 0 => return Err("notakto boards only hold X".into()),
+            //either player places either mark , any mix is fine
+            RuleSet::Notakto | RuleSet::Wild => {}
+            //X moves first , so X has as many marks as O or one more
+            _ if xs != os && xs != os + 1 => return Err("mark counts don't fit alternating moves".into()),
+            _ => {}
+        }
+        //X makes every even move , whatever it places
+        let x_to_move = (xs + os) % 2 == 0;
+
+        //what the board alone says , a finished game's turn is the player who made the last move
+        let mut game = GameState::from_parts(board, turn, RoomStatus::Playing, None).with_rules(rules);
+        game.evaluate();
+        let on_board = game.status;
+
+        match status {
+            RoomStatus::Waiting if xs + os > 0 => return Err("waiting game with marks on the board".into()),
+            RoomStatus::Playing if on_board != RoomStatus::Playing => {
+                return Err(format!("board is already {}", on_board));
+            }
+            RoomStatus::Playing if (turn == Mark::X) != x_to_move => {
+                return Err(format!("it can't be {}'s turn on this board", turn));
+            }
+            RoomStatus::Draw if on_board != RoomStatus::Draw => return Err("board is not a draw".into()),
+            //a line on the board decides the winner , without one the game was resigned
+            RoomStatus::Won if on_board == RoomStatus::Won && game.winner != winner => {
+                return Err("winner doesn't match the board".into());
+            }
+            RoomStatus::Won if on_board == RoomStatus::Draw => return Err("board is a draw".into()),
+            _ => {}
+        }
+        game.status = status;
+        game.winner = winner;
+        Ok(game)
+    }
+}
+
+impl GameState {
+    /// The board as one character per cell ('X' , 'O' or '-'),
+    /// the format of the rooms.board_state column.
+    pub fn cells(&self) -> String {
+        self.board
+            .iter()
+            .map(|c| c.map(Mark::as_char).unwrap_or('-'))
+            .collect()
+    }
+
+    pub fn board_from_cells(cells: &str) -> Result<[Option<Mark>; 9], String> {
+        let board: Vec<Option<Mark>> = cells
+            .chars()
+            .map(|c| match c {
+                '-' => Ok(None),
+                _ => board_mark(c).map(Some),
+            })
+            .collect::<Result<_, _>>()?;
+        board
+            .try_into()
+            .map_err(|_| format!("board needs 9 cells: {}", cells))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_multiplayer_marks() {
+        //used to get as far as the zobrist table and panic there
+        for position in ["Δ2/3/3 X playing 3x3k3", "□2/3/3 X playing 3x3k3", "3/3/3 Δ playing 3x3k3", "XXX/OO1/3 O won-Δ 3x3k3"] {
+            assert!(position.parse::<GameState>().is_err(), "{} parsed", position);
+        }
+        assert!(GameState::board_from_cells("Δ--------").is_err());
+    }
+
+    #[test]
+    fn positions_survive_printing_and_parsing() {
+        for position in [
+            "3/3/3 X waiting 3x3k3",
+            "X1O/3/3 X playing 3x3k3",
+            "XXX/OO1/3 X won-X 3x3k3",
+            "XOX/XOO/OXX O draw 3x3k3",
+            "X2/3/3 O won-O 3x3k3", //resigned , no line on the board
+        ] {
+            let game: GameState = position.parse().unwrap();
+            assert_eq!(game.to_string(), position);
+        }
+        let game: GameState = "X1O/3/3_X_playing_3x3k3".parse().unwrap();
+        assert_eq!(game.to_string(), "X1O/3/3 X playing 3x3k3");
+    }
+
+    #[test]
+    fn rule_sets_survive_printing_and_parsing() {
+        for (position, rules) in [
+            ("X1O/3/3 X playing 3x3k3-misere", RuleSet::Misere),
+            ("XXX/OO1/3 X won-O 3x3k3-misere", RuleSet::Misere), //the line loses
+            ("OO1/3/3 X playing 3x3k3-wild", RuleSet::Wild),
+            ("OOO/XX1/3 X won-X 3x3k3-wild", RuleSet::Wild), //whoever completes a line wins
+            ("XX1/1X1/3 O playing 3x3k3-notakto", RuleSet::Notakto),
+            ("XXX/3/3 X won-O 3x3k3-notakto", RuleSet::Notakto), //every mark is X , the line loses
+        ] {
+            let game: GameState = position.parse().unwrap();
+            assert_eq!(game.rules, rules);
+            assert_eq!(game.to_string(), position);
+        }
+    }
+
+    #[test]
+    fn rejects_positions_the_rule_set_cant_reach() {
+        for position in [
+            "XX1/3/3 O playing 3x3k3",         //two X in a row
+            "X1O/3/3 X playing 3x3k3-notakto", //O on a notakto board
+            "OO1/3/3 O playing 3x3k3-wild",    //two moves played , X is next
+            "XXX/OO1/3 X won-X 3x3k3-misere",  //the line loses under misere
+            "3/3/3 X waiting 3x3k3-fog",       //moves that bump into hidden marks
+            "3/3/3 X waiting 3x3k3-chess",
+            "3/3/3 X waiting 4x4k3-misere",
+        ] {
+            assert!(position.parse::<GameState>().is_err(), "{} parsed", position);
+        }
+    }
+}
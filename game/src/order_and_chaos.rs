@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{CellMove, Game, GameKind, GameOutcome, Mark, RoomStatus, RuleSet};
+
+pub const SIZE: usize = 6;
+const LINE: usize = 5;
+
+/// Order and chaos , the tic-tac-toe rule set played on a 6x6 board.
+/// Both players may place X or O. Order (the X seat , moves first) wins with five of
+/// one mark in a row , chaos (the O seat) wins by filling the board without that happening.
+/// As in the standard rules six in a row is an overline and doesn't count.
+#[derive(Serialize, Debug, Deserialize, Clone)]
+pub struct OrderAndChaos {
+    pub board: [[Option<Mark>; SIZE]; SIZE], //row 0 is the top
+    pub winner: Option<Mark>,
+    pub status: RoomStatus,
+    pub turn: Mark, //the seat to move , X is order and O is chaos
+    #[serde(default = "order_and_chaos")]
+    pub rules: RuleSet, //always order_and_chaos , sent so clients can tell it from plain tic-tac-toe
+}
+
+fn order_and_chaos() -> RuleSet {
+    RuleSet::OrderAndChaos
+}
+
+impl Default for OrderAndChaos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderAndChaos {
+    pub const ORDER: Mark = Mark::X;
+    pub const CHAOS: Mark = Mark::O;
+
+    pub fn new() -> Self {
+        Self {
+            board: [[None; SIZE]; SIZE],
+            winner: None,
+            status: RoomStatus::Waiting,
+            turn: Self::ORDER,
+            rules: RuleSet::OrderAndChaos,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.status == RoomStatus::Playing
+    }
+
+    pub fn start(&mut self) {
+        if self.status == RoomStatus::Waiting {
+            self.status = RoomStatus::Playing;
+        }
+    }
+
+    //`player` gives up , the other side wins
+    pub fn forfeit(&mut self, player: Mark) -> Result<(), String> {
+        if !self.is_playing() {
+            return Err("game is not in progress".into());
+        }
+        self.status = RoomStatus::Won;
+        self.winner = Some(player.other());
+        Ok(())
+    }
+
+    /// `player` puts `piece` on `position` (row * SIZE + column , counted from the top left).
+    pub fn place(&mut self, position: usize, player: Mark, piece: Option<Mark>) -> Result<(), String> {
+        if !self.is_playing() {
+            return Err("game is not started yet".into());
+        }
+        if player != self.turn {
+            return Err("its not your turn buddy".into());
+        }
+        if position >= SIZE * SIZE {
+            return Err("Invalid position".into());
+        }
+        let (row, column) = (position / SIZE, position % SIZE);
+        if self.board[row][column].is_some() {
+            return Err("cell is already accoupied ser choose another".into());
+        }
+        let mark = RuleSet::OrderAndChaos.piece(player, piece)?;
+        self.board[row][column] = Some(mark);
+
+        if self.connects(row, column, mark) {
+            self.status = RoomStatus::Won;
+            self.winner = Some(Self::ORDER);
+        } else if self.board.iter().flatten().all(|c| c.is_some()) {
+            self.status = RoomStatus::Won;
+            self.winner = Some(Self::CHAOS);
+        } else {
+            self.turn = self.turn.other();
+        }
+        Ok(())
+    }
+
+    //does the mark just placed at (row , column) make exactly five in a row ,
+    //a run of six through it doesn't but five along another direction still does
+    fn connects(&self, row: usize, column: usize, mark: Mark) -> bool {
+        const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+        let run = |dr: isize, dc: isize| {
+            (1..SIZE as isize)
+                .take_while(|i| {
+                    let (r, c) = (row as isize + dr * i, column as isize + dc * i);
+                    (0..SIZE as isize).contains(&r)
+                        && (0..SIZE as isize).contains(&c)
+                        && self.board[r as usize][c as usize] == Some(mark)
+                })
+                .count()
+        };
+        DIRECTIONS
+            .iter()
+            .any(|(dr, dc)| 1 + run(*dr, *dc) + run(-dr, -dc) == LINE)
+    }
+}
+
+impl Game for OrderAndChaos {
+    const KIND: GameKind = GameKind::TicTacToe;
+
+    type Move = CellMove;
+    type Payload = CellMove;
+
+    fn player_count(&self) -> usize {
+        2
+    }
+
+    fn to_move(&self) -> usize {
+        self.turn.seat()
+    }
+
+    fn legal_moves(&self) -> Vec<CellMove> {
+        if !self.is_playing() {
+            return Vec::new();
+        }
+        (0..SIZE * SIZE)
+            .filter(|p| self.board[p / SIZE][p % SIZE].is_none())
+            .flat_map(|position| [Mark::X, Mark::O].map(|mark| CellMove { position, mark: Some(mark) }))
+            .collect()
+    }
+
+    fn apply(&mut self, mv: CellMove) -> Result<(), String> {
+        let player = self.turn;
+        self.place(mv.position, player, mv.mark)
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        match (self.status, self.winner) {
+            (RoomStatus::Won, Some(mark)) => Some(GameOutcome::Win { seat: mark.seat() }),
+            _ => None,
+        }
+    }
+
+    fn start(&mut self) {
+        OrderAndChaos::start(self)
+    }
+
+    fn status(&self) -> RoomStatus {
+        self.status
+    }
+
+    fn forfeit(&mut self, seat: usize) -> Result<(), String> {
+        let mark = Mark::from_seat(seat).ok_or_else(|| format!("no player in seat {}", seat))?;
+        OrderAndChaos::forfeit(self, mark)
+    }
+
+    fn rules(&self) -> RuleSet {
+        RuleSet::OrderAndChaos
+    }
+
+    fn decode_move(payload: CellMove) -> Result<CellMove, String> {
+        Ok(payload)
+    }
+
+    fn encode_move(mv: CellMove) -> CellMove {
+        mv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started()->OrderAndChaos{
+        let mut game = OrderAndChaos::new();
+        game.start();
+        game
+    }
+
+    fn play(game: &mut OrderAndChaos, moves: &[(usize, Mark)]){
+        for (position, mark) in moves {
+            game.apply(CellMove { position: *position, mark: Some(*mark) }).unwrap();
+        }
+    }
+
+    #[test]
+    fn five_in_a_row_wins_for_order_whatever_the_mark(){
+        let mut game = started();
+        //order lines up Os on the top row , chaos plays on the bottom one
+        play(&mut game, &[(0,Mark::O),(30,Mark::X),(1,Mark::O),(31,Mark::O),(2,Mark::O),(32,Mark::X),(3,Mark::O),(33,Mark::O)]);
+        assert!(game.is_playing());
+        play(&mut game, &[(4,Mark::O)]);
+        assert_eq!(game.status, RoomStatus::Won);
+        assert_eq!(game.winner, Some(OrderAndChaos::ORDER));
+        assert_eq!(game.outcome(), Some(GameOutcome::Win { seat: 0 }));
+
+        //a line chaos finishes is still order's win
+        let mut game = started();
+        play(&mut game, &[(0,Mark::X),(6,Mark::X),(1,Mark::O),(12,Mark::X),(2,Mark::O),(18,Mark::X),(3,Mark::O),(24,Mark::X)]);
+        assert_eq!(game.winner, Some(OrderAndChaos::ORDER));
+    }
+
+    #[test]
+    fn six_in_a_row_is_not_a_win(){
+        let mut game = started();
+        play(&mut game, &[(0,Mark::X),(30,Mark::X),(1,Mark::X),(31,Mark::O),(2,Mark::X),(32,Mark::X),(4,Mark::X),(33,Mark::O),(5,Mark::X),(34,Mark::X)]);
+        play(&mut game, &[(3,Mark::X)]);
+        assert!(game.is_playing());
+        assert_eq!(game.winner, None);
+        assert_eq!(game.turn, OrderAndChaos::CHAOS);
+    }
+
+    #[test]
+    fn chaos_wins_by_filling_the_board(){
+        let rows = ["XXOOXX", "XXOOXX", "XXOOXX", "XXOOXX", "OOXXOO", "XXOOX-"];
+        let mut game = started();
+        for (position, c) in rows.concat().chars().enumerate() {
+            game.board[position / SIZE][position % SIZE] = Mark::from_char(c);
+        }
+        game.turn = OrderAndChaos::CHAOS;
+        assert_eq!(game.legal_moves().len(), 2);
+        play(&mut game, &[(35,Mark::X)]);
+        assert_eq!(game.status, RoomStatus::Won);
+        assert_eq!(game.winner, Some(OrderAndChaos::CHAOS));
+        assert_eq!(game.outcome(), Some(GameOutcome::Win { seat: 1 }));
+    }
+
+    #[test]
+    fn only_the_player_to_move_places_and_only_on_free_cells(){
+        let mut game = started();
+        assert!(game.place(0, OrderAndChaos::CHAOS, Some(Mark::X)).is_err());
+        game.place(0, OrderAndChaos::ORDER, Some(Mark::O)).unwrap();
+        assert!(game.place(0, OrderAndChaos::CHAOS, Some(Mark::X)).is_err());
+        assert!(game.place(SIZE * SIZE, OrderAndChaos::CHAOS, Some(Mark::X)).is_err());
+        assert_eq!(game.turn, OrderAndChaos::CHAOS);
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Game, GameKind, GameOutcome, Mark, RoomStatus, RuleSet};
+
+pub const CELLS: usize = 9;
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2], [3, 4, 5], [6, 7, 8],
+    [0, 3, 6], [1, 4, 7], [2, 5, 8],
+    [0, 4, 8], [2, 4, 6],
+];
+
+/// A mark with the number of the move that placed it (X1 , O2 , X3 ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuantumMark {
+    pub mark: Mark,
+    pub ply: u8,
+}
+
+/// A mark still in superposition , it sits in both cells until the entanglement collapses.
+/// Cells are the nodes of the entanglement graph and spooky marks its edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpookyMark {
+    pub mark: Mark,
+    pub ply: u8,
+    pub cells: [usize; 2],
+}
+
+/// Quantum tic-tac-toe. Every move puts a spooky mark in two cells , when one closes a cycle
+/// in the entanglement graph the opponent picks which of its two cells it lands on and
+/// everything entangled with it collapses into classical marks. Only classical marks make lines.
+///
+/// A collapse can complete lines for both players at once , the line whose newest mark
+/// is older wins and scores 1 , the other player still gets half a point.
+#[derive(Serialize, Debug, Deserialize, Clone)]
+pub struct QuantumState {
+    pub board: [Option<QuantumMark>; CELLS], //classical marks
+    pub spooky: Vec<SpookyMark>,
+    pub collapse: Option<SpookyMark>, //closed a cycle , waiting for the player to move to pick its cell
+    pub ply: u8,                      //marks placed so far
+    pub score: [f64; 2],              //points by seat , handed out when the game ends
+    pub winner: Option<Mark>,
+    pub status: RoomStatus,
+    pub turn: Mark,
+    #[serde(default = "quantum")]
+    pub rules: RuleSet, //always quantum , sent so clients can tell it from plain tic-tac-toe
+}
+
+fn quantum() -> RuleSet {
+    RuleSet::Quantum
+}
+
+impl Default for QuantumState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuantumState {
+    pub fn new() -> Self {
+        Self {
+            board: [None; CELLS],
+            spooky: Vec::new(),
+            collapse: None,
+            ply: 0,
+            score: [0.0; 2],
+            winner: None,
+            status: RoomStatus::Waiting,
+            turn: Mark::X,
+            rules: RuleSet::Quantum,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.status == RoomStatus::Playing
+    }
+
+    pub fn start(&mut self) {
+        if self.status == RoomStatus::Waiting {
+            self.status = RoomStatus::Playing;
+        }
+    }
+
+    //`mark` gives up , the other side takes the point
+    pub fn forfeit(&mut self, mark: Mark) -> Result<(), String> {
+        if !self.is_playing() {
+            return Err("game is not in progress".into());
+        }
+        self.status = RoomStatus::Won;
+        self.winner = Some(mark.other());
+        self.score = [0.0; 2];
+        self.score[mark.other().seat()] = 1.0;
+        Ok(())
+    }
+
+    fn check_turn(&self, player: Mark) -> Result<(), String> {
+        if !self.is_playing() {
+            return Err("game is not started yet".into());
+        }
+        if player != self.turn {
+            return Err("its not your turn buddy".into());
+        }
+        Ok(())
+    }
+
+    /// `player` puts a spooky mark in both `cells`.
+    pub fn place_spooky(&mut self, cells: [usize; 2], player: Mark) -> Result<(), String> {
+        self.check_turn(player)?;
+        if let Some(pending) = self.collapse {
+            return Err(format!("{}{} closed a cycle , pick the cell it collapses into first", pending.mark, pending.ply));
+        }
+        let [a, b] = cells;
+        if a >= CELLS || b >= CELLS {
+            return Err("Invalid position".into());
+        }
+        if a == b {
+            return Err("a spooky mark needs two different cells".into());
+        }
+        if self.board[a].is_some() || self.board[b].is_some() {
+            return Err("cell is already accoupied ser choose another".into());
+        }
+
+        //joining two cells that are already entangled closes a cycle
+        let cycle = self.entangled(a, b);
+        self.ply += 1;
+        let mark = SpookyMark { mark: player, ply: self.ply, cells };
+        self.spooky.push(mark);
+        if cycle {
+            self.collapse = Some(mark);
+        }
+        self.turn = self.turn.other();
+        Ok(())
+    }
+
+    /// `player` (the one who didn't close the cycle) lands the pending mark on `cell`.
+    /// They make their spooky move after this.
+    pub fn collapse_into(&mut self, cell: usize, player: Mark) -> Result<(), String> {
+        self.check_turn(player)?;
+        let pending = self.collapse.ok_or_else(|| "there is nothing to collapse".to_string())?;
+        if !pending.cells.contains(&cell) {
+            return Err(format!(
+                "{}{} can only collapse into cell {} or {}",
+                pending.mark, pending.ply, pending.cells[0], pending.cells[1]
+            ));
+        }
+        self.collapse = None;
+        self.resolve(pending, cell);
+        self.settle();
+
+        //one free cell left means no spooky move fits , the next mark goes straight onto it
+        let free: Vec<usize> = (0..CELLS).filter(|c| self.board[*c].is_none()).collect();
+        if self.is_playing()
+            && let [cell] = free[..]
+        {
+            self.ply += 1;
+            self.board[cell] = Some(QuantumMark { mark: self.turn, ply: self.ply });
+            self.settle();
+        }
+        Ok(())
+    }
+
+    //are `a` and `b` connected through spooky marks
+    fn entangled(&self, a: usize, b: usize) -> bool {
+        let mut seen = [false; CELLS];
+        let mut queue = VecDeque::from([a]);
+        seen[a] = true;
+        while let Some(cell) = queue.pop_front() {
+            if cell == b {
+                return true;
+            }
+            for mark in self.spooky.iter().filter(|m| m.cells.contains(&cell)) {
+                for next in mark.cells {
+                    if !seen[next] {
+                        seen[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    //land `first` on `cell` , every other spooky mark in a cell that just became classical
+    //is pushed into its other cell , until the whole entangled group is classical
+    fn resolve(&mut self, first: SpookyMark, cell: usize) {
+        self.spooky.retain(|m| m.ply != first.ply);
+        let mut queue = VecDeque::from([(first, cell)]);
+        while let Some((mark, cell)) = queue.pop_front() {
+            if self.board[cell].is_some() {
+                continue; //can't happen in a consistent graph , but never overwrite a classical mark
+            }
+            self.board[cell] = Some(QuantumMark { mark: mark.mark, ply: mark.ply });
+            let (touching, rest): (Vec<SpookyMark>, Vec<SpookyMark>) =
+                self.spooky.iter().partition(|m| m.cells.contains(&cell));
+            self.spooky = rest;
+            for m in touching {
+                let other = if m.cells[0] == cell { m.cells[1] } else { m.cells[0] };
+                queue.push_back((m, other));
+            }
+        }
+    }
+
+    //look for lines after a collapse and hand out the points
+    fn settle(&mut self) {
+        //per player the line that was finished first , judged by its newest mark
+        let mut first = [None::<u8>; 2];
+        for line in LINES {
+            let marks = line.map(|c| self.board[c]);
+            if let [Some(a), Some(b), Some(c)] = marks
+                && a.mark == b.mark
+                && b.mark == c.mark
+            {
+                let newest = a.ply.max(b.ply).max(c.ply);
+                let best = &mut first[a.mark.seat()];
+                *best = Some(best.map_or(newest, |p| p.min(newest)));
+            }
+        }
+
+        let winner = match first {
+            [Some(x), Some(o)] => {
+                //both made a line in the same collapse
+                let (winner, other) = if x < o { (Mark::X, Mark::O) } else { (Mark::O, Mark::X) };
+                self.score[other.seat()] = 0.5;
+                Some(winner)
+            }
+            [Some(_), None] => Some(Mark::X),
+            [None, Some(_)] => Some(Mark::O),
+            [None, None] => None,
+        };
+        match winner {
+            Some(mark) => {
+                self.score[mark.seat()] = 1.0;
+                self.status = RoomStatus::Won;
+                self.winner = Some(mark);
+            }
+            None if self.board.iter().all(|c| c.is_some()) => self.status = RoomStatus::Draw,
+            None => {}
+        }
+    }
+}
+
+/// Quantum move payload , a spooky mark `{"cells":[0,4]}` or the pick after a cycle `{"collapse":4}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QuantumMove {
+    Spooky { cells: [usize; 2] },
+    Collapse { collapse: usize },
+}
+
+impl Game for QuantumState {
+    const KIND: GameKind = GameKind::TicTacToe;
+
+    type Move = QuantumMove;
+    type Payload = QuantumMove;
+
+    fn player_count(&self) -> usize {
+        2
+    }
+
+    fn to_move(&self) -> usize {
+        self.turn.seat()
+    }
+
+    fn legal_moves(&self) -> Vec<QuantumMove> {
+        if !self.is_playing() {
+            return Vec::new();
+        }
+        if let Some(pending) = self.collapse {
+            return pending.cells.map(|collapse| QuantumMove::Collapse { collapse }).to_vec();
+        }
+        let free: Vec<usize> = (0..CELLS).filter(|c| self.board[*c].is_none()).collect();
+        free.iter()
+            .enumerate()
+            .flat_map(|(i, a)| free[i + 1..].iter().map(|b| QuantumMove::Spooky { cells: [*a, *b] }))
+            .collect()
+    }
+
+    fn apply(&mut self, mv: QuantumMove) -> Result<(), String> {
+        let player = self.turn;
+        match mv {
+            QuantumMove::Spooky { cells } => self.place_spooky(cells, player),
+            QuantumMove::Collapse { collapse } => self.collapse_into(collapse, player),
+        }
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        match (self.status, self.winner) {
+            (RoomStatus::Won, Some(mark)) => Some(GameOutcome::Win { seat: mark.seat() }),
+            (RoomStatus::Draw, _) => Some(GameOutcome::Draw),
+            _ => None,
+        }
+    }
+
+    fn start(&mut self) {
+        QuantumState::start(self)
+    }
+
+    fn status(&self) -> RoomStatus {
+        self.status
+    }
+
+    fn forfeit(&mut self, seat: usize) -> Result<(), String> {
+        let mark = Mark::from_seat(seat).ok_or_else(|| format!("no player in seat {}", seat))?;
+        QuantumState::forfeit(self, mark)
+    }
+
+    fn rules(&self) -> RuleSet {
+        RuleSet::Quantum
+    }
+
+    fn decode_move(payload: QuantumMove) -> Result<QuantumMove, String> {
+        Ok(payload)
+    }
+
+    fn encode_move(mv: QuantumMove) -> QuantumMove {
+        mv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started() -> QuantumState {
+        let mut game = QuantumState::new();
+        game.start();
+        game
+    }
+
+    #[test]
+    fn closing_a_cycle_collapses_the_entangled_marks() {
+        let mut game = started();
+        game.place_spooky([0, 1], Mark::X).unwrap();
+        game.place_spooky([1, 2], Mark::O).unwrap();
+        game.place_spooky([2, 0], Mark::X).unwrap(); //0-1-2-0 is a cycle
+        assert!(game.place_spooky([3, 4], Mark::O).is_err(), "moved before collapsing");
+        assert!(game.collapse_into(5, Mark::O).is_err());
+
+        //X3 lands on 0 , X1 is pushed out to 1 and O2 to 2
+        game.collapse_into(0, Mark::O).unwrap();
+        assert!(game.spooky.is_empty());
+        assert_eq!(game.board[0], Some(QuantumMark { mark: Mark::X, ply: 3 }));
+        assert_eq!(game.board[1], Some(QuantumMark { mark: Mark::X, ply: 1 }));
+        assert_eq!(game.board[2], Some(QuantumMark { mark: Mark::O, ply: 2 }));
+        assert_eq!(game.turn, Mark::O);
+        assert!(game.is_playing());
+    }
+
+    //spooky marks in a chain , the last one closes the cycle and the collapse lands every mark
+    fn chain(game: &mut QuantumState, cells: &[[usize; 2]]) {
+        for pair in cells {
+            let player = game.turn;
+            game.place_spooky(*pair, player).unwrap();
+        }
+    }
+
+    #[test]
+    fn a_collapse_finishing_lines_for_both_gives_the_older_one_the_win() {
+        let mut game = started();
+        chain(&mut game, &[[0, 3], [3, 1], [1, 4], [4, 2], [2, 5], [5, 0]]);
+        assert!(game.collapse.is_some());
+
+        //O6 lands on 5 and pushes the rest along the cycle:
+        //X1 X3 X5 take the top row , O2 O4 O6 the middle one
+        game.collapse_into(5, Mark::X).unwrap();
+        let marks: Vec<_> = game.board[..6].iter().map(|c| c.map(|m| m.mark)).collect();
+        assert_eq!(marks, [Some(Mark::X), Some(Mark::X), Some(Mark::X), Some(Mark::O), Some(Mark::O), Some(Mark::O)]);
+        //X's line was done at move 5 , O's only at move 6
+        assert_eq!(game.status, RoomStatus::Won);
+        assert_eq!(game.winner, Some(Mark::X));
+        assert_eq!(game.score, [1.0, 0.5]);
+        assert_eq!(Game::outcome(&game), Some(GameOutcome::Win { seat: 0 }));
+    }
+
+    #[test]
+    fn the_last_free_cell_is_filled_for_the_player_to_move() {
+        let mut game = started();
+        chain(&mut game, &[[0, 1], [1, 2], [2, 4], [4, 3], [3, 5], [5, 7], [7, 6], [6, 0]]);
+
+        //eight classical marks and no line , only cell 8 is left and no spooky mark fits in one cell
+        game.collapse_into(6, Mark::X).unwrap();
+        assert_eq!(game.board[8], Some(QuantumMark { mark: Mark::X, ply: 9 }));
+        assert_eq!(game.board[6], Some(QuantumMark { mark: Mark::O, ply: 8 }));
+        assert_eq!(game.status, RoomStatus::Draw);
+        assert_eq!(game.winner, None);
+        assert_eq!(game.score, [0.0, 0.0]);
+    }
+}
@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Game, GameKind, GameOutcome, Mark, RoomStatus};
+
+pub const SIZE: usize = 4;
+pub const CELLS: usize = SIZE * SIZE * SIZE;
+pub const LINE_COUNT: usize = 76;
+
+/// Every winning line of the cube , as cell indexes (see [`Qubic::cell`]):
+/// 48 rows along the axes , 24 diagonals of the planes and the 4 through the middle.
+pub const LINES: [[usize; SIZE]; LINE_COUNT] = make_lines();
+
+//a line starts wherever a step back along its direction leaves the cube and three steps forward stay in it
+const fn make_lines() -> [[usize; SIZE]; LINE_COUNT] {
+    //one of each pair of opposite directions
+    const DIRECTIONS: [(isize, isize, isize); 13] = [
+        (1, 0, 0), (0, 1, 0), (0, 0, 1),
+        (1, 1, 0), (1, -1, 0), (1, 0, 1), (1, 0, -1), (0, 1, 1), (0, 1, -1),
+        (1, 1, 1), (1, 1, -1), (1, -1, 1), (1, -1, -1),
+    ];
+    const fn inside(v: isize) -> bool {
+        v >= 0 && v < SIZE as isize
+    }
+    let mut lines = [[0; SIZE]; LINE_COUNT];
+    let mut count = 0;
+    let mut d = 0;
+    while d < DIRECTIONS.len() {
+        let (dx, dy, dz) = DIRECTIONS[d];
+        let mut cell = 0;
+        while cell < CELLS {
+            let (x, y, z) = ((cell % SIZE) as isize, (cell / SIZE % SIZE) as isize, (cell / (SIZE * SIZE)) as isize);
+            let last = (x + 3 * dx, y + 3 * dy, z + 3 * dz);
+            let before = (x - dx, y - dy, z - dz);
+            if inside(last.0) && inside(last.1) && inside(last.2)
+                && !(inside(before.0) && inside(before.1) && inside(before.2))
+            {
+                let mut i = 0;
+                while i < SIZE {
+                    let (cx, cy, cz) = (x + i as isize * dx, y + i as isize * dy, z + i as isize * dz);
+                    lines[count][i] = (cx + cy * SIZE as isize + cz * (SIZE * SIZE) as isize) as usize;
+                    i += 1;
+                }
+                count += 1;
+            }
+            cell += 1;
+        }
+        d += 1;
+    }
+    assert!(count == LINE_COUNT);
+    lines
+}
+
+/// Qubic , tic-tac-toe in a 4x4x4 cube. Four in a row along any of the 76 lines wins.
+#[derive(Serialize, Debug, Deserialize, Clone)]
+pub struct Qubic {
+    pub board: [[[Option<Mark>; SIZE]; SIZE]; SIZE], //board[z][y][x] , one 4x4 layer per z
+    pub winner: Option<Mark>,
+    pub status: RoomStatus,
+    pub turn: Mark,
+}
+
+impl Default for Qubic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Qubic {
+    pub fn new() -> Self {
+        Self::from_parts([[[None; SIZE]; SIZE]; SIZE], Mark::X, RoomStatus::Waiting, None)
+    }
+
+    pub fn from_parts(board: [[[Option<Mark>; SIZE]; SIZE]; SIZE], turn: Mark, status: RoomStatus, winner: Option<Mark>) -> Self {
+        Self { board, winner, status, turn }
+    }
+
+    /// Index of (x , y , z) counting x fastest , z is the layer. None off the cube.
+    pub fn cell(x: usize, y: usize, z: usize) -> Option<usize> {
+        (x < SIZE && y < SIZE && z < SIZE).then_some(x + y * SIZE + z * SIZE * SIZE)
+    }
+
+    pub fn coords(cell: usize) -> (usize, usize, usize) {
+        (cell % SIZE, cell / SIZE % SIZE, cell / (SIZE * SIZE))
+    }
+
+    pub fn at(&self, cell: usize) -> Option<Mark> {
+        let (x, y, z) = Self::coords(cell);
+        self.board[z][y][x]
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.status == RoomStatus::Playing
+    }
+
+    pub fn start(&mut self) {
+        if self.status == RoomStatus::Waiting {
+            self.status = RoomStatus::Playing;
+        }
+    }
+
+    //`mark` gives up , the other side wins
+    pub fn forfeit(&mut self, mark: Mark) -> Result<(), String> {
+        if !self.is_playing() {
+            return Err("game is not in progress".into());
+        }
+        self.status = RoomStatus::Won;
+        self.winner = Some(mark.other());
+        Ok(())
+    }
+
+    pub fn place(&mut self, cell: usize, mark: Mark) -> Result<(), String> {
+        if !self.is_playing() {
+            return Err("game is not started yet".into());
+        }
+        if mark != self.turn {
+            return Err("its not your turn buddy".into());
+        }
+        if cell >= CELLS {
+            return Err("Invalid position".into());
+        }
+        if self.at(cell).is_some() {
+            return Err("cell is already accoupied ser choose another".into());
+        }
+        let (x, y, z) = Self::coords(cell);
+        self.board[z][y][x] = Some(mark);
+
+        //only lines through the new mark can have been completed
+        let won = LINES
+            .iter()
+            .filter(|line| line.contains(&cell))
+            .any(|line| line.iter().all(|c| self.at(*c) == Some(mark)));
+        if won {
+            self.status = RoomStatus::Won;
+            self.winner = Some(mark);
+        } else if (0..CELLS).all(|c| self.at(c).is_some()) {
+            self.status = RoomStatus::Draw;
+        } else {
+            self.turn = self.turn.other();
+        }
+        Ok(())
+    }
+
+    //one character per cell in cell index order , like GameState::cells
+    pub fn cells(&self) -> String {
+        (0..CELLS)
+            .map(|c| self.at(c).map(Mark::as_char).unwrap_or('-'))
+            .collect()
+    }
+
+    pub fn board_from_cells(cells: &str) -> Result<[[[Option<Mark>; SIZE]; SIZE]; SIZE], String> {
+        if cells.chars().count() != CELLS {
+            return Err(format!("board needs {} cells: {}", CELLS, cells));
+        }
+        let mut board = [[[None; SIZE]; SIZE]; SIZE];
+        for (i, c) in cells.chars().enumerate() {
+            let (x, y, z) = Self::coords(i);
+            board[z][y][x] = match c {
+                '-' => None,
+                _ => Some(Mark::from_char(c).ok_or_else(|| format!("invalid cell: {}", c))?),
+            };
+        }
+        Ok(board)
+    }
+}
+
+/// Qubic move payload , `{"x":0,"y":3,"z":1}`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CubeMove {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
+}
+
+impl Game for Qubic {
+    const KIND: GameKind = GameKind::Qubic;
+
+    type Move = usize;
+    type Payload = CubeMove;
+
+    fn player_count(&self) -> usize {
+        2
+    }
+
+    fn to_move(&self) -> usize {
+        self.turn.seat()
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        if !self.is_playing() {
+            return Vec::new();
+        }
+        (0..CELLS).filter(|c| self.at(*c).is_none()).collect()
+    }
+
+    fn apply(&mut self, cell: usize) -> Result<(), String> {
+        let mark = self.turn;
+        self.place(cell, mark)
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        match (self.status, self.winner) {
+            (RoomStatus::Won, Some(mark)) => Some(GameOutcome::Win { seat: mark.seat() }),
+            (RoomStatus::Draw, _) => Some(GameOutcome::Draw),
+            _ => None,
+        }
+    }
+
+    fn start(&mut self) {
+        Qubic::start(self)
+    }
+
+    fn status(&self) -> RoomStatus {
+        self.status
+    }
+
+    fn forfeit(&mut self, seat: usize) -> Result<(), String> {
+        let mark = Mark::from_seat(seat).ok_or_else(|| format!("no player in seat {}", seat))?;
+        Qubic::forfeit(self, mark)
+    }
+
+    fn decode_move(payload: CubeMove) -> Result<usize, String> {
+        Qubic::cell(payload.x, payload.y, payload.z).ok_or_else(|| "Invalid position".to_string())
+    }
+
+    fn encode_move(cell: usize) -> CubeMove {
+        let (x, y, z) = Qubic::coords(cell);
+        CubeMove { x, y, z }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn the_cube_has_76_straight_lines() {
+        let mut seen = HashSet::new();
+        for line in LINES {
+            //four cells a fixed step apart along each axis
+            let coords = line.map(Qubic::coords);
+            let step = |a: (usize, usize, usize), b: (usize, usize, usize)| {
+                (b.0 as isize - a.0 as isize, b.1 as isize - a.1 as isize, b.2 as isize - a.2 as isize)
+            };
+            let first = step(coords[0], coords[1]);
+            assert_ne!(first, (0, 0, 0));
+            assert!(coords.windows(2).all(|w| step(w[0], w[1]) == first), "{:?} is not a line", line);
+            let mut cells = line;
+            cells.sort();
+            assert!(seen.insert(cells), "{:?} is there twice", line);
+        }
+        assert_eq!(seen.len(), 76);
+    }
+
+    #[test]
+    fn a_diagonal_through_the_middle_wins() {
+        let mut game = Qubic::new();
+        game.start();
+        for (x, o) in [(0, 1), (21, 2), (42, 3)] {
+            game.place(x, Mark::X).unwrap();
+            game.place(o, Mark::O).unwrap();
+        }
+        game.place(63, Mark::X).unwrap();
+        assert_eq!(game.status, RoomStatus::Won);
+        assert_eq!(game.winner, Some(Mark::X));
+    }
+}
@@ -0,0 +1,105 @@
+// Elo ratings that also work for more than two players.
+// A game of n players counts as every pair of them playing each other once ,
+// the better placed one of a pair scores 1 (0.5 each when they share a place).
+// K is split over the n - 1 pairings each player is in , so a four player game
+// moves a rating about as far as a single two player one.
+
+/// Rating of a player who hasn't finished a rated game yet.
+pub const INITIAL_RATING: i32 = 1200;
+/// Most a two player game can move a rating.
+pub const K_FACTOR: f64 = 32.0;
+
+/// Chance the elo formula gives a player rated `rating` of beating one rated `opponent`.
+pub fn expected_score(rating: i32, opponent: i32) -> f64 {
+    1.0 / (1.0 + 10f64.powf(f64::from(opponent - rating) / 400.0))
+}
+
+/// New ratings after a game , `places` by seat like [`crate::Game::places`] (0 is first).
+/// `ratings` and `places` line up seat by seat.
+pub fn rate(ratings: &[i32], places: &[usize]) -> Vec<i32> {
+    let n = ratings.len().min(places.len());
+    if n < 2 {
+        return ratings.to_vec();
+    }
+    let k = K_FACTOR / (n - 1) as f64;
+    let deltas: Vec<f64> = (0..n)
+        .map(|i| {
+            let delta: f64 = (0..n)
+                .filter(|j| *j != i)
+                .map(|j| {
+                    let score = match places[i].cmp(&places[j]) {
+                        std::cmp::Ordering::Less => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Greater => 0.0,
+                    };
+                    score - expected_score(ratings[i], ratings[j])
+                })
+                .sum();
+            k * delta
+        })
+        .collect();
+
+    //the changes add up to 0 but rounding each one on its own can make or lose a point ,
+    //it is handed back by whoever was rounded the furthest in that direction
+    let mut rounded: Vec<i32> = deltas.iter().map(|d| d.round() as i32).collect();
+    let mut drift: i32 = rounded.iter().sum();
+    while drift != 0 {
+        let step = drift.signum();
+        let furthest = (0..n)
+            .max_by(|a, b| {
+                let over = |i: usize| f64::from(step) * (f64::from(rounded[i]) - deltas[i]);
+                over(*a).total_cmp(&over(*b))
+            })
+            .unwrap_or(0);
+        rounded[furthest] -= step;
+        drift -= step;
+    }
+    (0..n).map(|i| ratings[i] + rounded[i]).collect()
+}
+
+/// New ratings after a game with teams , `teams` holds the ratings of each seat's players.
+/// A seat plays at the average rating of its players and each of them moves by the seat's change ,
+/// so a team is rated as one player and one member can't gain more than another.
+pub fn rate_teams(teams: &[Vec<i32>], places: &[usize]) -> Vec<Vec<i32>> {
+    let average: Vec<i32> = teams
+        .iter()
+        .map(|team| match team.len() {
+            0 => INITIAL_RATING,
+            n => (f64::from(team.iter().sum::<i32>()) / n as f64).round() as i32,
+        })
+        .collect();
+    let rated = rate(&average, places);
+    teams
+        .iter()
+        .zip(average.iter().zip(&rated))
+        .map(|(team, (before, after))| team.iter().map(|r| r + after - before).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn team_members_move_together() {
+        let rated = rate_teams(&[vec![1300, 1100], vec![1200, 1200]], &[0, 1]);
+        assert_eq!(rated[0][0] - 1300, rated[0][1] - 1100);
+        assert_eq!(rated[1][0], rated[1][1]);
+        //the teams average the same , so it is an even game
+        assert_eq!(rated, vec![vec![1316, 1116], vec![1184, 1184]]);
+    }
+
+    #[test]
+    fn multiplayer_changes_add_up_to_zero() {
+        for ratings in [[1200, 1200, 1200, 1200], [1000, 1400, 1237, 1350], [1111, 1555, 900, 1201]] {
+            for places in [[0, 1, 2, 3], [0, 1, 1, 3], [2, 0, 3, 1], [0, 0, 2, 2], [3, 2, 1, 0]] {
+                let rated = rate(&ratings, &places);
+                let total: i32 = rated.iter().zip(&ratings).map(|(after, before)| after - before).sum();
+                assert_eq!(total, 0, "{:?} placed {:?} went to {:?}", ratings, places, rated);
+            }
+        }
+        //three players , the winner gains what the other two lose
+        let rated = rate(&[1200, 1200, 1200], &[0, 1, 2]);
+        assert_eq!(rated, vec![1216, 1200, 1184]);
+    }
+}
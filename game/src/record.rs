@@ -0,0 +1,358 @@
+use std::{fmt, str::FromStr};
+
+use crate::{CellMove, GameState, Mark, RoomStatus, RuleSet};
+
+/// Variant of a record without a Variant tag.
+pub const STANDARD_VARIANT: &str = "standard";
+
+/// Termination tag of a game a player gave up (resigned or left) ,
+/// its moves stop short of the result so the loser is taken to resign after the last one.
+pub const RESIGNATION: &str = "resignation";
+
+// Squares are named like a chess board: files a-c from left to right ,
+// ranks 1-3 from bottom to top. Board index 0 (top left) is a3 , 4 is b2 , 8 is c1.
+pub fn square_name(position: usize) -> String {
+    let file = (b'a' + (position % 3) as u8) as char;
+    let rank = 3 - position / 3;
+    format!("{}{}", file, rank)
+}
+
+pub fn parse_square(s: &str) -> Result<usize, String> {
+    let mut chars = s.chars();
+    let (Some(file), Some(rank), None) = (chars.next(), chars.next(), chars.next()) else {
+        return Err(format!("invalid square: {}", s));
+    };
+    let col = match file {
+        'a'..='c' => file as usize - 'a' as usize,
+        _ => return Err(format!("invalid square: {}", s)),
+    };
+    let row = match rank {
+        '1'..='3' => 3 - (rank as usize - '0' as usize),
+        _ => return Err(format!("invalid square: {}", s)),
+    };
+    Ok(row * 3 + col)
+}
+
+//a move token , the square with the piece in front when the player picked it ("b2" , "Ob2")
+fn move_token(mv: &CellMove) -> String {
+    match mv.mark {
+        Some(mark) => format!("{}{}", mark, square_name(mv.position)),
+        None => square_name(mv.position),
+    }
+}
+
+fn parse_move(s: &str) -> Result<CellMove, String> {
+    let mut chars = s.chars();
+    match chars.next().and_then(Mark::from_char) {
+        Some(mark) => Ok(CellMove {
+            position: parse_square(chars.as_str())?,
+            mark: Some(mark),
+        }),
+        None => parse_square(s).map(CellMove::new),
+    }
+}
+
+/// How a recorded game ended , written like PGN results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GameResult {
+    XWins,
+    OWins,
+    Draw,
+    #[default]
+    Ongoing,
+}
+
+impl GameResult {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GameResult::XWins => "1-0",
+            GameResult::OWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Ongoing => "*",
+        }
+    }
+
+    pub fn from_state(game: &GameState) -> Self {
+        Self::from_outcome(game.status, game.winner)
+    }
+
+    pub fn from_outcome(status: RoomStatus, winner: Option<Mark>) -> Self {
+        match (status, winner) {
+            (RoomStatus::Won, Some(Mark::X)) => GameResult::XWins,
+            (RoomStatus::Won, Some(Mark::O)) => GameResult::OWins,
+            (RoomStatus::Draw, _) => GameResult::Draw,
+            _ => GameResult::Ongoing,
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GameResult {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1-0" => Ok(GameResult::XWins),
+            "0-1" => Ok(GameResult::OWins),
+            "1/2-1/2" => Ok(GameResult::Draw),
+            "*" => Ok(GameResult::Ongoing),
+            _ => Err(format!("invalid result: {}", s)),
+        }
+    }
+}
+
+/// A game in the portable text format (PGN-like):
+///
+/// ```text
+/// [Event "tik-tok-toe"]
+/// [Date "2025.12.11"]
+/// [X "alice"]
+/// [O "bob"]
+/// [Variant "standard"]
+/// [TimeControl "-"]
+/// [Termination "normal"]
+/// [Result "0-1"]
+///
+/// 1. b2 a1 2. c3 c1 3. a3 b1 0-1
+/// ```
+///
+/// Tags keep their order and unknown tags are kept as they are.
+/// X always moves first , so the moves alternate X , O , X ...
+/// The Variant tag names the rule set , in wild games each move is written with the
+/// piece placed in front of the square (`1. Ob2 Xa1`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GameRecord {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<CellMove>, //in the order they were played
+    pub result: GameResult,
+}
+
+impl GameRecord {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    //replaces the value when the tag is already there
+    pub fn set_tag(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = value,
+            None => self.tags.push((name.to_string(), value)),
+        }
+    }
+
+    pub fn variant(&self) -> &str {
+        self.tag("Variant").unwrap_or(STANDARD_VARIANT)
+    }
+
+    //the rule set named by the Variant tag , only ones whose moves are single squares can be written down
+    pub fn rules(&self) -> Result<RuleSet, String> {
+        match self.variant().parse::<RuleSet>() {
+            Ok(rules) if rules.single_cell_moves() => Ok(rules),
+            _ => Err(format!("unsupported variant: {}", self.variant())),
+        }
+    }
+
+    /// Play the moves from an empty board and check they are legal
+    /// and end the way the result says.
+    pub fn replay(&self) -> Result<GameState, String> {
+        let mut game = GameState::new().with_rules(self.rules()?);
+        game.start();
+        for (i, mv) in self.moves.iter().enumerate() {
+            let player = game.turn;
+            game.place(mv.position, player, mv.mark)
+                .map_err(|e| format!("move {} ({}): {}", i + 1, move_token(mv), e))?;
+        }
+        if game.is_playing() && self.tag("Termination") == Some(RESIGNATION) {
+            match self.result {
+                GameResult::XWins => game.forfeit(Mark::O)?,
+                GameResult::OWins => game.forfeit(Mark::X)?,
+                _ => {}
+            }
+        }
+        let result = GameResult::from_state(&game);
+        if result != self.result {
+            return Err(format!("moves end in {} but the result is {}", result, self.result));
+        }
+        Ok(game)
+    }
+
+    /// Parse a file holding any number of games , like a bulk export.
+    pub fn parse_many(s: &str) -> Result<Vec<Self>, String> {
+        let mut games = Vec::new();
+        let mut current = String::new();
+        let mut in_moves = false;
+        for line in s.lines() {
+            let trimmed = line.trim();
+            //a tag after movetext starts the next game
+            if trimmed.starts_with('[') && in_moves {
+                games.push(current.parse()?);
+                current.clear();
+                in_moves = false;
+            }
+            if !trimmed.is_empty() && !trimmed.starts_with('[') {
+                in_moves = true;
+            }
+            current.push_str(line);
+            current.push('\n');
+        }
+        if !current.trim().is_empty() {
+            games.push(current.parse()?);
+        }
+        Ok(games)
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn parse_tag(line: &str) -> Result<(String, String), String> {
+    let inner = line
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or_else(|| format!("invalid tag: {}", line))?;
+    let (name, value) = inner
+        .split_once(' ')
+        .ok_or_else(|| format!("invalid tag: {}", line))?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(|| format!("tag value must be quoted: {}", line))?;
+
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            unescaped.extend(chars.next());
+        } else {
+            unescaped.push(c);
+        }
+    }
+    Ok((name.to_string(), unescaped))
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, escape(value))?;
+        }
+        if self.tag("Result").is_none() {
+            writeln!(f, "[Result \"{}\"]", self.result)?;
+        }
+        writeln!(f)?;
+
+        for (i, mv) in self.moves.iter().enumerate() {
+            if i % 2 == 0 {
+                write!(f, "{}. ", i / 2 + 1)?;
+            }
+            write!(f, "{} ", move_token(mv))?;
+        }
+        writeln!(f, "{}", self.result)
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut record = GameRecord::new();
+        let mut result = None;
+
+        for line in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if line.starts_with('[') {
+                let (name, value) = parse_tag(line)?;
+                record.tags.push((name, value));
+                continue;
+            }
+            for token in line.split_whitespace() {
+                if result.is_some() {
+                    return Err(format!("unexpected text after the result: {}", token));
+                }
+                //move numbers are only there for people , "1." or "1.b2"
+                let token = match token.split_once('.') {
+                    Some((n, rest)) if n.chars().all(|c| c.is_ascii_digit()) => rest,
+                    _ => token,
+                };
+                if token.is_empty() {
+                    continue;
+                }
+                match token.parse::<GameResult>() {
+                    Ok(r) => result = Some(r),
+                    Err(_) => record.moves.push(parse_move(token)?),
+                }
+            }
+        }
+
+        let tagged = record.tag("Result").map(str::parse::<GameResult>).transpose()?;
+        record.result = match (tagged, result) {
+            (Some(a), Some(b)) if a != b => {
+                return Err(format!("Result tag says {} but the moves end in {}", a, b));
+            }
+            (Some(r), _) | (None, Some(r)) => r,
+            (None, None) => GameResult::Ongoing,
+        };
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(variant: &str, moves: Vec<CellMove>, result: GameResult) -> GameRecord {
+        let mut record = GameRecord::new();
+        record.set_tag("Event", "tik-tok-toe");
+        record.set_tag("X", "alice \"the\" \\first");
+        record.set_tag("O", "bob");
+        record.set_tag("Variant", variant);
+        record.set_tag("Result", result.as_str());
+        record.moves = moves;
+        record.result = result;
+        record
+    }
+
+    #[test]
+    fn records_survive_printing_and_parsing() {
+        let standard = record("standard", [4, 6, 2, 8, 0, 7].map(CellMove::new).to_vec(), GameResult::OWins);
+        let wild = record(
+            "wild",
+            vec![CellMove { position: 4, mark: Some(Mark::O) }, CellMove { position: 6, mark: Some(Mark::X) }],
+            GameResult::Ongoing,
+        );
+        for game in [&standard, &wild] {
+            assert_eq!(game.to_string().parse::<GameRecord>().as_ref(), Ok(game));
+        }
+        let export = format!("{}\n{}", standard, wild);
+        assert_eq!(GameRecord::parse_many(&export), Ok(vec![standard.clone(), wild]));
+        assert_eq!(standard.replay().map(|g| g.winner), Ok(Some(Mark::O)));
+    }
+
+    #[test]
+    fn resigned_games_replay_to_their_result() {
+        let mut resigned = record("standard", [4, 0].map(CellMove::new).to_vec(), GameResult::OWins);
+        //no line on the board , the moves alone don't reach 0-1
+        assert!(resigned.replay().is_err());
+
+        resigned.set_tag("Termination", RESIGNATION);
+        let parsed: GameRecord = resigned.to_string().parse().unwrap();
+        let game = parsed.replay().unwrap();
+        assert_eq!(game.status, RoomStatus::Won);
+        assert_eq!(game.winner, Some(Mark::O));
+
+        //resigning doesn't turn a board that is still going into a draw
+        let mut drawn = record("standard", [4, 0].map(CellMove::new).to_vec(), GameResult::Draw);
+        drawn.set_tag("Termination", RESIGNATION);
+        assert!(drawn.replay().is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{CellMove, GameState, Mark, Outcome, RuleSet, Solver, square_name};
+
+/// How a move compares to perfect play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveTag {
+    Best,
+    Mistake,   //keeps the outcome but wins slower / loses faster than needed
+    MissedWin, //a win was there , the game is only a draw now
+    Blunder,   //turns a won or drawn position into a lost one
+}
+
+impl MoveTag {
+    //share of a perfect move this counts for in the accuracy
+    fn credit(self) -> f64 {
+        match self {
+            MoveTag::Best => 1.0,
+            MoveTag::Mistake => 0.5,
+            MoveTag::MissedWin | MoveTag::Blunder => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewedMove {
+    pub ply: usize,
+    pub position: usize,
+    pub square: String,
+    pub mark: Mark, //the player who moved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub piece: Option<Mark>, //what they placed , when the rule set let them pick
+    pub tag: MoveTag,
+    pub before: Outcome, //what the mover had before the move
+    pub after: Outcome,  //what the mover has after it
+    pub best_moves: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerReview {
+    pub mark: Mark,
+    pub moves: usize,
+    pub best: usize,
+    pub mistakes: usize,
+    pub missed_wins: usize,
+    pub blunders: usize,
+    pub accuracy: f64, //percent , best moves count fully and mistakes half
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameReview {
+    pub moves: Vec<ReviewedMove>,
+    pub players: Vec<PlayerReview>, //X then O
+}
+
+impl Solver {
+    /// Replay `moves` from an empty board played by `rules` and grade every one against the solver.
+    pub fn review(&mut self, rules: RuleSet, moves: &[CellMove]) -> Result<GameReview, String> {
+        let mut game = GameState::new().with_rules(rules);
+        game.start();
+
+        let mut reviewed = Vec::with_capacity(moves.len());
+        for (i, mv) in moves.iter().enumerate() {
+            let analysis = self.analyze(&game);
+            let chosen = analysis
+                .moves
+                .iter()
+                .find(|m| m.position == mv.position && m.mark == mv.mark)
+                .ok_or_else(|| format!("move {} ({}) is not legal", i + 1, square_name(mv.position)))?;
+            let best_score = analysis.moves.first().map(|m| m.score);
+
+            let tag = match (analysis.outcome, chosen.outcome) {
+                _ if Some(chosen.score) == best_score => MoveTag::Best,
+                (before, after) if before == after => MoveTag::Mistake,
+                (_, Outcome::Loss) => MoveTag::Blunder,
+                _ => MoveTag::MissedWin,
+            };
+            reviewed.push(ReviewedMove {
+                ply: i + 1,
+                position: mv.position,
+                square: square_name(mv.position),
+                mark: game.turn,
+                piece: mv.mark,
+                tag,
+                before: analysis.outcome,
+                after: chosen.outcome,
+                best_moves: analysis.best_moves,
+            });
+
+            let player = game.turn;
+            game.place(mv.position, player, mv.mark)?;
+        }
+
+        let players = [Mark::X, Mark::O]
+            .into_iter()
+            .map(|mark| player_review(mark, &reviewed))
+            .collect();
+        Ok(GameReview { moves: reviewed, players })
+    }
+}
+
+fn player_review(mark: Mark, moves: &[ReviewedMove]) -> PlayerReview {
+    let own: Vec<&ReviewedMove> = moves.iter().filter(|m| m.mark == mark).collect();
+    let count = |tag: MoveTag| own.iter().filter(|m| m.tag == tag).count();
+    let credit: f64 = own.iter().map(|m| m.tag.credit()).sum();
+
+    PlayerReview {
+        mark,
+        moves: own.len(),
+        best: count(MoveTag::Best),
+        mistakes: count(MoveTag::Mistake),
+        missed_wins: count(MoveTag::MissedWin),
+        blunders: count(MoveTag::Blunder),
+        accuracy: if own.is_empty() {
+            100.0
+        } else {
+            (credit / own.len() as f64 * 1000.0).round() / 10.0
+        },
+    }
+}
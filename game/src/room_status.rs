@@ -0,0 +1,54 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// Lifecycle of a room / game.
+/// waiting -> playing -> won | draw
+/// Stored as the postgres enum type `room_status`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "room_status", rename_all = "lowercase"))]
+pub enum RoomStatus {
+    #[default]
+    Waiting,
+    Playing,
+    Won,
+    Draw,
+}
+
+impl RoomStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RoomStatus::Waiting => "waiting",
+            RoomStatus::Playing => "playing",
+            RoomStatus::Won => "won",
+            RoomStatus::Draw => "draw",
+        }
+    }
+
+    //game has ended , no more moves
+    pub fn is_finished(self) -> bool {
+        matches!(self, RoomStatus::Won | RoomStatus::Draw)
+    }
+}
+
+impl fmt::Display for RoomStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RoomStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "waiting" => Ok(RoomStatus::Waiting),
+            "playing" => Ok(RoomStatus::Playing),
+            "won" => Ok(RoomStatus::Won),
+            "draw" => Ok(RoomStatus::Draw),
+            _ => Err(format!("invalid room status: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::{any::Any, fmt};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    GameKind, GameState, Mark, RoomStatus, RuleSet,
+    mcts::{self, MctsConfig},
+};
+
+/// How a finished game ended. Players are numbered by seat , 0 moves first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum GameOutcome {
+    Win { seat: usize },
+    Draw,
+}
+
+/// A move as it travels in client messages and the event log ,
+/// the fields next to `type` / `room_id` , e.g. `{"position":4}`.
+pub type MovePayload = serde_json::Map<String, serde_json::Value>;
+
+/// The rules of a turn-based game. Enough for the engines to search it
+/// and for a room to host it without knowing anything about the board.
+pub trait Game: Clone {
+    const KIND: GameKind;
+
+    type Move: Copy + PartialEq + fmt::Debug;
+    /// Typed form of a move payload , decoded from / encoded to a [`MovePayload`].
+    type Payload: Serialize + DeserializeOwned;
+
+    fn player_count(&self) -> usize;
+    /// Seat of the player whose turn it is.
+    fn to_move(&self) -> usize;
+    /// Every move the player to move may make , empty unless the game is in progress.
+    fn legal_moves(&self) -> Vec<Self::Move>;
+    /// Play `mv` for the player to move.
+    fn apply(&mut self, mv: Self::Move) -> Result<(), String>;
+    /// None while the game is still going.
+    fn outcome(&self) -> Option<GameOutcome>;
+    /// Finishing place of every seat once the game is over , 0 is first and tied seats share a place.
+    /// The winner is first and everybody else shares second , unless the game knows better.
+    fn places(&self) -> Option<Vec<usize>> {
+        let players = self.player_count();
+        match self.outcome()? {
+            GameOutcome::Win { seat } => Some((0..players).map(|s| usize::from(s != seat)).collect()),
+            GameOutcome::Draw => Some(vec![0; players]),
+        }
+    }
+
+    /// Every seat is taken , leave the waiting state.
+    fn start(&mut self);
+    fn status(&self) -> RoomStatus;
+    /// `seat` gives up , resigned or ran out of time.
+    fn forfeit(&mut self, seat: usize) -> Result<(), String>;
+    /// The rule set being played , only tic-tac-toe has more than one.
+    fn rules(&self) -> RuleSet {
+        RuleSet::Standard
+    }
+    /// The game as `seat` is allowed to see it , None for spectators.
+    /// Everything unless the game hides information.
+    fn view(&self, _seat: Option<usize>) -> Self {
+        self.clone()
+    }
+
+    fn decode_move(payload: Self::Payload) -> Result<Self::Move, String>;
+    fn encode_move(mv: Self::Move) -> Self::Payload;
+}
+
+pub fn decode_payload<G: Game>(payload: &MovePayload) -> Result<G::Move, String> {
+    let payload = serde_json::from_value(serde_json::Value::Object(payload.clone()))
+        .map_err(|e| format!("invalid move: {}", e))?;
+    G::decode_move(payload)
+}
+
+pub fn encode_payload<G: Game>(mv: G::Move) -> MovePayload {
+    match serde_json::to_value(G::encode_move(mv)) {
+        Ok(serde_json::Value::Object(fields)) => fields,
+        _ => MovePayload::new(),
+    }
+}
+
+/// Object safe face of a [`Game`] , what a room holds.
+/// Moves and state go in and out as json so the room code is the same for every game.
+pub trait RoomGame: Send {
+    fn kind(&self) -> GameKind;
+    fn player_count(&self) -> usize;
+    fn to_move(&self) -> usize;
+    fn status(&self) -> RoomStatus;
+    fn outcome(&self) -> Option<GameOutcome>;
+    fn places(&self) -> Option<Vec<usize>>;
+    fn winner(&self) -> Option<Mark> {
+        match self.outcome() {
+            Some(GameOutcome::Win { seat }) => Mark::from_seat(seat),
+            _ => None,
+        }
+    }
+    fn is_playing(&self) -> bool {
+        self.status() == RoomStatus::Playing
+    }
+    fn start(&mut self);
+    fn forfeit(&mut self, seat: usize) -> Result<(), String>;
+    fn rules(&self) -> RuleSet;
+    /// The serialized game state sent to clients (board , turn , status , ...).
+    fn state(&self) -> serde_json::Value;
+    /// The state as `seat` sees it (see [`Game::view`]) , what goes to that player.
+    fn state_for(&self, seat: Option<usize>) -> serde_json::Value;
+    /// Play a move for `seat` , returns the move as it should be echoed back.
+    fn play(&mut self, seat: usize, payload: &MovePayload) -> Result<MovePayload, String>;
+    /// Pick a move for the player to move with MCTS , from what that seat can see.
+    fn search_move(&self, config: &MctsConfig) -> Option<MovePayload>;
+    fn clone_box(&self) -> Box<dyn RoomGame>;
+    /// For the game specific extras (the solver only knows tic-tac-toe).
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<G> RoomGame for G
+where
+    G: Game + Serialize + Send + Sync + 'static,
+    G::Move: Send,
+{
+    fn kind(&self) -> GameKind {
+        G::KIND
+    }
+
+    fn player_count(&self) -> usize {
+        Game::player_count(self)
+    }
+
+    fn to_move(&self) -> usize {
+        Game::to_move(self)
+    }
+
+    fn status(&self) -> RoomStatus {
+        Game::status(self)
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        Game::outcome(self)
+    }
+
+    fn places(&self) -> Option<Vec<usize>> {
+        Game::places(self)
+    }
+
+    fn start(&mut self) {
+        Game::start(self)
+    }
+
+    fn forfeit(&mut self, seat: usize) -> Result<(), String> {
+        Game::forfeit(self, seat)
+    }
+
+    fn rules(&self) -> RuleSet {
+        Game::rules(self)
+    }
+
+    fn state(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn state_for(&self, seat: Option<usize>) -> serde_json::Value {
+        serde_json::to_value(Game::view(self, seat)).unwrap_or_default()
+    }
+
+    fn play(&mut self, seat: usize, payload: &MovePayload) -> Result<MovePayload, String> {
+        if Game::status(self) != RoomStatus::Playing {
+            return Err("game is not started yet".into());
+        }
+        if seat != Game::to_move(self) {
+            return Err("its not your turn buddy".into());
+        }
+        let mv = decode_payload::<G>(payload)?;
+        self.apply(mv)?;
+        Ok(encode_payload::<G>(mv))
+    }
+
+    fn search_move(&self, config: &MctsConfig) -> Option<MovePayload> {
+        let view = Game::view(self, Some(Game::to_move(self)));
+        mcts::search(&view, config).best_move.map(encode_payload::<G>)
+    }
+
+    fn clone_box(&self) -> Box<dyn RoomGame> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Clone for Box<dyn RoomGame> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Tic-tac-toe move payload , `{"position":4}`.
+/// Rule sets where players pick the piece add it , clients send `{"position":4,"mark":"O"}`.
+/// It is echoed and logged as `piece` , `mark` next to a move always names the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellMove {
+    pub position: usize,
+    #[serde(default, rename = "piece", alias = "mark", skip_serializing_if = "Option::is_none")]
+    pub mark: Option<Mark>,
+}
+
+impl CellMove {
+    pub fn new(position: usize) -> Self {
+        Self { position, mark: None }
+    }
+}
+
+impl Game for GameState {
+    const KIND: GameKind = GameKind::TicTacToe;
+
+    type Move = CellMove;
+    type Payload = CellMove;
+
+    fn player_count(&self) -> usize {
+        2
+    }
+
+    fn to_move(&self) -> usize {
+        self.turn.seat()
+    }
+
+    fn legal_moves(&self) -> Vec<CellMove> {
+        if !self.is_playing() {
+            return Vec::new();
+        }
+        let pieces: &[Option<Mark>] = if self.rules.chooses_mark() {
+            &[Some(Mark::X), Some(Mark::O)]
+        } else {
+            &[None]
+        };
+        (0..self.board.len())
+            .filter(|p| self.board[*p].is_none())
+            .flat_map(|position| pieces.iter().map(move |mark| CellMove { position, mark: *mark }))
+            .collect()
+    }
+
+    fn apply(&mut self, mv: CellMove) -> Result<(), String> {
+        let player = self.turn;
+        self.place(mv.position, player, mv.mark)
+    }
+
+    fn outcome(&self) -> Option<GameOutcome> {
+        match (self.status, self.winner) {
+            (RoomStatus::Won, Some(mark)) => Some(GameOutcome::Win { seat: mark.seat() }),
+            (RoomStatus::Draw, _) => Some(GameOutcome::Draw),
+            _ => None,
+        }
+    }
+
+    fn start(&mut self) {
+        GameState::start(self)
+    }
+
+    fn status(&self) -> RoomStatus {
+        self.status
+    }
+
+    fn forfeit(&mut self, seat: usize) -> Result<(), String> {
+        let mark = Mark::from_seat(seat).ok_or_else(|| format!("no player in seat {}", seat))?;
+        GameState::forfeit(self, mark)
+    }
+
+    fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    fn decode_move(payload: CellMove) -> Result<CellMove, String> {
+        Ok(payload)
+    }
+
+    fn encode_move(mv: CellMove) -> CellMove {
+        mv
+    }
+}
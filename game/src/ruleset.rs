@@ -0,0 +1,152 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Mark;
+
+/// Rule variant a room plays , picked when the room is created.
+/// Most only change what a move may place and who a finished line counts for ,
+/// order and chaos , quantum , fog and the multiplayer ones have their own game state.
+/// Stored as the postgres enum type `rule_set`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "rule_set", rename_all = "snake_case"))]
+pub enum RuleSet {
+    #[default]
+    Standard,
+    Misere,        //completing three in a row loses
+    Wild,          //either player places X or O , whoever completes a line wins
+    Notakto,       //both players place X , whoever completes a line loses
+    OrderAndChaos, //6x6 , order wants five in a row of either mark , chaos wants a full board without one
+    Quantum,       //marks go into two cells at once until a cycle collapses them , see QuantumState
+    Fog,           //players only see their own marks , playing into a hidden mark reveals it and costs the turn
+    FogRetry,      //same but after revealing a hidden mark the player goes again
+    Elimination,   //multiplayer , completing a line knocks you out and the last player left wins
+}
+
+impl RuleSet {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RuleSet::Standard => "standard",
+            RuleSet::Misere => "misere",
+            RuleSet::Wild => "wild",
+            RuleSet::Notakto => "notakto",
+            RuleSet::OrderAndChaos => "order_and_chaos",
+            RuleSet::Quantum => "quantum",
+            RuleSet::Fog => "fog",
+            RuleSet::FogRetry => "fog_retry",
+            RuleSet::Elimination => "elimination",
+        }
+    }
+
+    //every move is one cell of the 3x3 board , what the record format and the solver know
+    //a fog move can bump into a hidden mark , which no move list can hold
+    pub fn single_cell_moves(self) -> bool {
+        !matches!(self, RuleSet::OrderAndChaos | RuleSet::Quantum | RuleSet::Fog | RuleSet::FogRetry | RuleSet::Elimination)
+    }
+
+    //players can't see each other's marks until the game is over
+    pub fn hidden(self) -> bool {
+        matches!(self, RuleSet::Fog | RuleSet::FogRetry)
+    }
+
+    //players choose which mark to place
+    pub fn chooses_mark(self) -> bool {
+        matches!(self, RuleSet::Wild | RuleSet::OrderAndChaos)
+    }
+
+    /// The mark that goes on the board when `player` moves , `chosen` is the one the move asked for.
+    pub fn piece(self, player: Mark, chosen: Option<Mark>) -> Result<Mark, String> {
+        let fixed = match self {
+            //the 3x3 and 6x6 boards only have two marks , the multiplayer ones are never a piece
+            RuleSet::Wild | RuleSet::OrderAndChaos => {
+                return match chosen {
+                    Some(mark @ (Mark::X | Mark::O)) => Ok(mark),
+                    _ => Err(format!("{} moves need a mark , X or O", self)),
+                };
+            }
+            RuleSet::Notakto => Mark::X,
+            RuleSet::Standard | RuleSet::Misere | RuleSet::Quantum | RuleSet::Fog | RuleSet::FogRetry | RuleSet::Elimination => player,
+        };
+        match chosen {
+            Some(mark) if mark != fixed => Err(format!("you can only place {} in {}", fixed, self)),
+            _ => Ok(fixed),
+        }
+    }
+
+    /// Who wins when `mover` completes a line (of any mark) on the 3x3 board.
+    pub fn line_winner(self, line: Mark, mover: Mark) -> Mark {
+        match self {
+            RuleSet::Standard | RuleSet::Quantum | RuleSet::Fog | RuleSet::FogRetry => line,
+            RuleSet::Elimination => line.other(),
+            RuleSet::Misere => line.other(),
+            RuleSet::Wild | RuleSet::OrderAndChaos => mover,
+            RuleSet::Notakto => mover.other(),
+        }
+    }
+
+    //mixed into position hashes so a solver shared by several rule sets keeps them apart
+    pub fn hash_key(self) -> u64 {
+        match self {
+            RuleSet::Standard => 0,
+            other => (other as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15),
+        }
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(RuleSet::Standard),
+            "misere" => Ok(RuleSet::Misere),
+            "wild" => Ok(RuleSet::Wild),
+            "notakto" => Ok(RuleSet::Notakto),
+            "order_and_chaos" => Ok(RuleSet::OrderAndChaos),
+            "quantum" => Ok(RuleSet::Quantum),
+            "fog" => Ok(RuleSet::Fog),
+            "fog_retry" => Ok(RuleSet::FogRetry),
+            "elimination" => Ok(RuleSet::Elimination),
+            _ => Err(format!("unknown rule set: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameState, MovePayload, RoomGame};
+
+    fn payload(json: serde_json::Value) -> MovePayload {
+        match json {
+            serde_json::Value::Object(fields) => fields,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn chosen_pieces_are_x_or_o() {
+        for rules in [RuleSet::Wild, RuleSet::OrderAndChaos] {
+            assert_eq!(rules.piece(Mark::X, Some(Mark::O)), Ok(Mark::O));
+            assert!(rules.piece(Mark::X, Some(Mark::Triangle)).is_err());
+            assert!(rules.piece(Mark::O, Some(Mark::Square)).is_err());
+        }
+    }
+
+    #[test]
+    fn wild_room_rejects_a_triangle() {
+        //the move a client sent that used to panic the room manager
+        let mut game: Box<dyn RoomGame> = Box::new(GameState::new().with_rules(RuleSet::Wild));
+        game.start();
+        assert!(game.play(0, &payload(serde_json::json!({"position":4,"mark":"Δ"}))).is_err());
+        assert!(game.play(0, &payload(serde_json::json!({"position":4,"mark":"O"}))).is_ok());
+    }
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{CellMove, Game, GameState, Mark, RoomStatus, TranspositionTable, square_name};
+
+/// Theoretical result of a position with perfect play , from the side to move's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Outcome {
+    //the same result seen by the other player
+    pub fn flip(self) -> Self {
+        match self {
+            Outcome::Win => Outcome::Loss,
+            Outcome::Draw => Outcome::Draw,
+            Outcome::Loss => Outcome::Win,
+        }
+    }
+}
+
+/// Outcome plus how many plies it takes to get there when both sides play perfectly
+/// (the winner hurries , the loser holds on as long as possible).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Evaluation {
+    pub outcome: Outcome,
+    pub distance: u32,
+}
+
+impl Evaluation {
+    //single number to compare evaluations by , higher is better for the side to move
+    pub fn score(self) -> i32 {
+        match self.outcome {
+            Outcome::Win => 100 - self.distance as i32,
+            Outcome::Draw => 0,
+            Outcome::Loss => self.distance as i32 - 100,
+        }
+    }
+
+    //what playing into this position (evaluated for the opponent) means for the mover
+    fn for_mover(self) -> Self {
+        Self {
+            outcome: self.outcome.flip(),
+            distance: self.distance + 1,
+        }
+    }
+}
+
+/// One legal move and where it leads , from the mover's point of view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveScore {
+    pub position: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mark: Option<Mark>, //the piece to place , only for rule sets where the player picks it
+    pub square: String,
+    pub outcome: Outcome,
+    pub distance: u32,
+    pub score: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Analysis {
+    pub to_move: Mark,
+    pub outcome: Outcome,
+    pub distance: u32,
+    pub best_moves: Vec<usize>, //every move that keeps the best score
+    pub moves: Vec<MoveScore>,  //all legal moves , best first
+}
+
+/// Exhaustive search over a position , under whatever rule set the game plays. Results are cached in a transposition table
+/// under the canonical hash (rotations and mirrors share an entry),
+/// so one solver can be reused for many positions of the same game.
+#[derive(Default)]
+pub struct Solver {
+    table: TranspositionTable<Evaluation>,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            table: TranspositionTable::new(capacity),
+        }
+    }
+
+    pub fn evaluate(&mut self, game: &GameState) -> Evaluation {
+        match game.status {
+            //turn isn't switched when a game ends , so this is seen by the side that didn't make the last move ,
+            //they lost unless the line counts against whoever made it (misere , notakto)
+            RoomStatus::Won if game.winner == Some(game.turn) => return Evaluation { outcome: Outcome::Loss, distance: 0 },
+            RoomStatus::Won => return Evaluation { outcome: Outcome::Win, distance: 0 },
+            RoomStatus::Draw => return Evaluation { outcome: Outcome::Draw, distance: 0 },
+            _ => {}
+        }
+        let key = game.canonical_hash();
+        if let Some(eval) = self.table.get(key) {
+            return *eval;
+        }
+
+        let best = game
+            .legal_moves()
+            .into_iter()
+            .map(|mv| self.evaluate_move(game, mv))
+            .max_by_key(|eval| eval.score())
+            .unwrap_or(Evaluation { outcome: Outcome::Draw, distance: 0 });
+
+        self.table.insert(key, best);
+        best
+    }
+
+    /// Evaluate the position and every legal move in it.
+    /// A waiting game is analysed as if it had just started.
+    pub fn analyze(&mut self, game: &GameState) -> Analysis {
+        let mut game = game.clone();
+        game.start();
+
+        let eval = self.evaluate(&game);
+        let mut moves: Vec<MoveScore> = game
+            .legal_moves()
+            .into_iter()
+            .map(|mv| {
+                let eval = self.evaluate_move(&game, mv);
+                MoveScore {
+                    position: mv.position,
+                    mark: mv.mark,
+                    square: square_name(mv.position),
+                    outcome: eval.outcome,
+                    distance: eval.distance,
+                    score: eval.score(),
+                }
+            })
+            .collect();
+        moves.sort_by_key(|m| (-m.score, m.position, m.mark.map(Mark::seat)));
+
+        //a square counts once even when either piece on it keeps the best score (wild)
+        let mut best_moves: Vec<usize> = moves
+            .iter()
+            .filter(|m| m.score == eval.score())
+            .map(|m| m.position)
+            .collect();
+        best_moves.dedup();
+
+        let to_move = match game.status {
+            RoomStatus::Won => game.turn.other(),
+            _ => game.turn,
+        };
+        Analysis {
+            to_move,
+            outcome: eval.outcome,
+            distance: eval.distance,
+            best_moves,
+            moves,
+        }
+    }
+}
+
+impl Solver {
+    //what `mv` leads to for the player making it
+    fn evaluate_move(&mut self, game: &GameState, mv: CellMove) -> Evaluation {
+        let mut next = game.clone();
+        //only called with legal moves , so it always applies
+        let _ = next.apply(mv);
+        self.evaluate(&next).for_mover()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotated_positions_hit_the_table() {
+        let mut solver = Solver::new();
+        let corner: GameState = "X2/3/3 O playing 3x3k3".parse().unwrap();
+        let eval = solver.evaluate(&corner);
+        let solved = solver.table.len();
+        assert!(solved > 0);
+
+        //the same position turned a quarter , found without searching again
+        let rotated: GameState = "2X/3/3 O playing 3x3k3".parse().unwrap();
+        assert_eq!(solver.evaluate(&rotated), eval);
+        assert_eq!(solver.table.len(), solved);
+        assert_eq!(eval.outcome, Outcome::Draw);
+    }
+}
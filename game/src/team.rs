@@ -0,0 +1,31 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Players sharing each mark in a team room.
+pub const TEAM_SIZE: usize = 2;
+
+/// How the members of a team share their seat , picked when the room is created.
+/// Players join round the table , with two seats players 0 and 2 are X and 1 and 3 are O.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamPlay {
+    #[default]
+    Alternate, //members take the team's moves in turn
+    Consult,   //any member proposes a move , a teammate has to confirm it before it is played
+}
+
+impl TeamPlay {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TeamPlay::Alternate => "alternate",
+            TeamPlay::Consult => "consult",
+        }
+    }
+}
+
+impl fmt::Display for TeamPlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,83 @@
+/// Fixed-size hash table keyed by position hashes (see zobrist).
+/// Each hash maps to one slot , a new entry simply replaces whatever was there ,
+/// so memory never grows past the capacity picked up front.
+/// The solver caches its evaluations in one under GameState::canonical_hash , it is the only user:
+/// the ws hints share a single solver and so do the /analysis and game review routes.
+/// MCTS plays boards far too big to key by whole positions and keeps no table.
+/// V is whatever the owner wants to remember about a position.
+pub struct TranspositionTable<V> {
+    slots: Vec<Option<(u64, V)>>,
+    len: usize,
+}
+
+impl<V: Clone> TranspositionTable<V> {
+    pub const DEFAULT_CAPACITY: usize = 1 << 16;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: vec![None; capacity.max(1)],
+            len: 0,
+        }
+    }
+
+    fn slot(&self, hash: u64) -> usize {
+        (hash % self.slots.len() as u64) as usize
+    }
+
+    pub fn get(&self, hash: u64) -> Option<&V> {
+        match &self.slots[self.slot(hash)] {
+            //the full hash is kept so two positions sharing a slot are told apart
+            Some((h, v)) if *h == hash => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&mut self, hash: u64, value: V) {
+        let slot = self.slot(hash);
+        if self.slots[slot].is_none() {
+            self.len += 1;
+        }
+        self.slots[slot] = Some((hash, value));
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|s| *s = None);
+        self.len = 0;
+    }
+}
+
+impl<V: Clone> Default for TranspositionTable<V> {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_found_until_replaced() {
+        let mut table = TranspositionTable::new(8);
+        table.insert(3, "a");
+        assert_eq!(table.get(3), Some(&"a"));
+        //same slot , different position
+        assert_eq!(table.get(11), None);
+        table.insert(11, "b");
+        assert_eq!(table.get(11), Some(&"b"));
+        assert_eq!(table.get(3), None);
+        assert_eq!(table.len(), 1);
+    }
+}
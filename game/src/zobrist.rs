@@ -0,0 +1,128 @@
+use crate::Mark;
+
+// Zobrist hashing: every (cell , mark) pair gets a fixed random key and a board's
+// hash is the xor of the keys of its marks , so a move updates it with one xor.
+// The keys come from a fixed seed so hashes are the same in every process
+// (they can be stored and compared across servers).
+//
+// Only the 3x3 board is hashed , the solver is the one thing keeping a table of positions
+// and it can't search anything bigger.
+
+const CELLS: usize = 9;
+const MARKS: usize = Mark::ALL.len();
+
+pub(crate) const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+//every mark has keys even though the board only holds X and O ,
+//the first mark to move has no turn key so an empty board hashes to 0
+struct Keys {
+    cells: [[u64; MARKS]; CELLS],
+    turns: [u64; MARKS],
+}
+
+const fn make_keys() -> Keys {
+    let mut cells = [[0; MARKS]; CELLS];
+    let mut state = 0x7469_6B74_6F6B_746F; //"tiktokto"
+    let mut i = 0;
+    while i < CELLS {
+        let mut m = 0;
+        while m < MARKS {
+            let (s, key) = splitmix64(state);
+            cells[i][m] = key;
+            state = s;
+            m += 1;
+        }
+        i += 1;
+    }
+    let mut turns = [0; MARKS];
+    let mut m = 1;
+    while m < MARKS {
+        let (s, key) = splitmix64(state);
+        turns[m] = key;
+        state = s;
+        m += 1;
+    }
+    Keys { cells, turns }
+}
+
+const KEYS: Keys = make_keys();
+
+/// Key of `mark` standing on `cell`.
+pub fn cell_key(cell: usize, mark: Mark) -> u64 {
+    KEYS.cells[cell][mark.seat()]
+}
+
+/// Mixed in while it is `turn`'s move , so the same marks with a different side to move hash differently.
+pub fn turn_key(turn: Mark) -> u64 {
+    KEYS.turns[turn.seat()]
+}
+
+/// Full hash of a position.
+pub fn hash(board: &[Option<Mark>; CELLS], turn: Mark) -> u64 {
+    board
+        .iter()
+        .enumerate()
+        .filter_map(|(cell, mark)| mark.map(|m| cell_key(cell, m)))
+        .fold(turn_key(turn), |h, k| h ^ k)
+}
+
+/// The 8 symmetries of the square board (4 rotations , each optionally mirrored).
+/// `SYMMETRIES[s][i]` is the cell that ends up at `i` , so `transformed[i] = board[SYMMETRIES[s][i]]`.
+pub const SYMMETRIES: [[usize; CELLS]; 8] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8], //identity
+    [6, 3, 0, 7, 4, 1, 8, 5, 2], //rotate 90
+    [8, 7, 6, 5, 4, 3, 2, 1, 0], //rotate 180
+    [2, 5, 8, 1, 4, 7, 0, 3, 6], //rotate 270
+    [2, 1, 0, 5, 4, 3, 8, 7, 6], //mirror left-right
+    [6, 7, 8, 3, 4, 5, 0, 1, 2], //mirror top-bottom
+    [0, 3, 6, 1, 4, 7, 2, 5, 8], //main diagonal
+    [8, 5, 2, 7, 4, 1, 6, 3, 0], //anti diagonal
+];
+
+/// Where each cell goes under each symmetry , the inverse of SYMMETRIES:
+/// a mark put on `cell` lands on `IMAGES[s][cell]` of the transformed board.
+pub const IMAGES: [[usize; CELLS]; 8] = {
+    let mut images = [[0; CELLS]; 8];
+    let mut s = 0;
+    while s < SYMMETRIES.len() {
+        let mut i = 0;
+        while i < CELLS {
+            images[s][SYMMETRIES[s][i]] = i;
+            i += 1;
+        }
+        s += 1;
+    }
+    images
+};
+
+pub fn transform(board: &[Option<Mark>; CELLS], symmetry: usize) -> [Option<Mark>; CELLS] {
+    SYMMETRIES[symmetry].map(|from| board[from])
+}
+
+/// Hash of every symmetric version of the position , in SYMMETRIES order (the first is the board as it is).
+/// GameState keeps these up to date move by move , the smallest is the key rotations and mirrors share.
+pub fn symmetric_hashes(board: &[Option<Mark>; CELLS], turn: Mark) -> [u64; 8] {
+    std::array::from_fn(|s| hash(&transform(board, s), turn))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_undo_the_symmetries() {
+        for s in 0..SYMMETRIES.len() {
+            for cell in 0..CELLS {
+                assert_eq!(SYMMETRIES[s][IMAGES[s][cell]], cell);
+            }
+        }
+        //an empty board with X to move hashes to 0 in every symmetry
+        assert_eq!(symmetric_hashes(&[None; CELLS], Mark::X), [0; 8]);
+    }
+}
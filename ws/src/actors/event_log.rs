@@ -0,0 +1,120 @@
+use actix::{Actor, AsyncContext, AtomicResponse, Context, Handler, Message, WrapFuture};
+use db::{AnyDb, EventRepo, MoveRepo, RoomRepo, UserRepo, models::{Move, Room as RoomRow}};
+use game::{GameEvent, Mark};
+use uuid::Uuid;
+
+//append one event to a room's log
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct RecordEvent{
+    pub room_id:Uuid,
+    pub event:GameEvent
+}
+
+//a game ended , update the ratings of its players
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct RateGame{
+    pub room_id:Uuid,
+    pub seats:Vec<Vec<Uuid>>, //players of every seat , more than one in team games
+    pub places:Vec<usize>  //same order , 0 is first
+}
+
+//play a move on the db row of an invite room , the row checks it against its own board first
+#[derive(Message)]
+#[rtype(result = "Result<RoomRow,MoveRejected>")]
+pub struct ApplyMove{
+    pub room_id:Uuid,
+    pub user_id:Uuid,
+    pub position:usize, //see db::models::move_position
+    pub expected_version:i32 //the row version the room last saw
+}
+
+//why the row turned a move down
+pub enum MoveRejected{
+    Invalid(String), //against the rules , or the row is gone
+    Stale{ row:RoomRow, moves:Vec<Move> } //the row changed since the room last saw it , this is how it is now
+}
+
+//the game in an invite room ended without a move , the row has to know too
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct FinishRoom{
+    pub room_id:Uuid,
+    pub winner:Option<Mark>
+}
+
+/// Writes game events to the db for the RoomManager.
+/// Messages are handled one at a time (ctx.wait) so the log keeps the order
+/// the RoomManager sent them in , and the RoomManager never waits on the db.
+pub struct EventLog{
+    db:AnyDb
+}
+
+impl EventLog{
+    pub fn new(db:AnyDb)->Self{
+        Self { db }
+    }
+}
+
+impl Actor for EventLog{
+    type Context = Context<Self>;
+}
+
+impl Handler<RecordEvent> for EventLog{
+    type Result = ();
+    fn handle(&mut self, msg: RecordEvent, ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+        //wait blocks the mailbox until the insert is done , the next event can't overtake this one
+        ctx.wait(async move {
+            if let Err(e) = db.append_event(msg.room_id, &msg.event).await {
+                log::error!("Failed to record {} event for room {}: {}", msg.event.kind(), msg.room_id, e);
+            }
+        }.into_actor(self));
+    }
+}
+
+impl Handler<RateGame> for EventLog{
+    type Result = ();
+    fn handle(&mut self, msg: RateGame, ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+        //in line with the events so the ratings are written after the last move
+        ctx.wait(async move {
+            match db.update_ratings(&msg.seats, &msg.places).await {
+                Ok(ratings) => log::info!("Rated game in room {}: {:?}", msg.room_id, ratings),
+                Err(e) => log::error!("Failed to rate game in room {}: {}", msg.room_id, e)
+            }
+        }.into_actor(self));
+    }
+}
+
+impl Handler<ApplyMove> for EventLog{
+    type Result = AtomicResponse<Self, Result<RoomRow,MoveRejected>>;
+    fn handle(&mut self, msg: ApplyMove, _: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+        //atomic like ctx.wait , the move is written in line with the events sent before it
+        AtomicResponse::new(Box::pin(async move {
+            match db.apply_move(msg.room_id, msg.user_id, msg.position, Some(msg.expected_version)).await {
+                Ok((row, _)) => Ok(row),
+                Err(e) if db::is_conflict(&e) => {
+                    let row = db.get_room_by_room_id(msg.room_id).await.map_err(|e|MoveRejected::Invalid(e.to_string()))?;
+                    let moves = db.get_moves(msg.room_id).await.map_err(|e|MoveRejected::Invalid(e.to_string()))?;
+                    Err(MoveRejected::Stale{ row, moves })
+                }
+                Err(e) => Err(MoveRejected::Invalid(e.to_string()))
+            }
+        }.into_actor(self)))
+    }
+}
+
+impl Handler<FinishRoom> for EventLog{
+    type Result = ();
+    fn handle(&mut self, msg: FinishRoom, ctx: &mut Self::Context) -> Self::Result {
+        let db = self.db.clone();
+        ctx.wait(async move {
+            if let Err(e) = db.finish_room(msg.room_id, msg.winner).await {
+                log::error!("Failed to finish room {}: {}", msg.room_id, e);
+            }
+        }.into_actor(self));
+    }
+}
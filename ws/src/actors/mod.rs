@@ -2,4 +2,7 @@ pub mod ws_client;
 pub use ws_client::*;
 
 pub mod room_manager;
-pub use room_manager::*;
\ No newline at end of file
+pub use room_manager::*;
+
+pub mod event_log;
+pub use event_log::*;
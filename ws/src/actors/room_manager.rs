@@ -1,17 +1,40 @@
-use std::{collections::HashMap};
-use actix::{Actor, Addr, Context, Handler, Message};
+use std::{collections::{HashMap, HashSet, hash_map::Entry}, sync::{Arc, Mutex, MutexGuard}, time::Duration};
+use actix::{Actor, ActorFutureExt, Addr, AsyncContext, AtomicResponse, Context, Handler, Message, ResponseActFuture, WrapFuture};
+use game::{Analysis, CellMove, GameEvent, GameKind, GameState, Mark, MovePayload, RuleSet, Solver, TeamPlay, decode_payload, mcts::MctsConfig};
+use chrono::Utc;
+use db::{AnyDb, EventRepo, RoomRepo, models::{Move, Room as RoomRow}};
 use uuid::Uuid;
 
-use crate::{Room, RoomMessage, WsClient};
+use crate::{ApplyMove, EventLog, FinishRoom, LobbyRoom, MoveRejected, Proposal, RateGame, Room, RecordEvent, RoomMessage, WsClient};
 
 
 
+//a room resolved from an invite code in the db
+pub struct InvitedRoom{
+    pub room_id:Uuid,
+    pub game:GameKind,
+    pub rules:RuleSet,
+    pub password:Option<String>,
+    pub player_x_id:Uuid, //the creator plays X , whoever else comes in through the invite O
+    pub version:i32,
+    pub fresh:bool //nothing played on the row yet , a room can only be set up from a fresh one
+}
+
 #[derive(Message)]
 #[rtype(result="Result<Uuid,String>")]   // Returns room ID on success, error on failure
 pub struct JoinRoom{
     pub room_id:Option<Uuid>,
     pub user_id :Uuid,
-    pub addr : Addr<WsClient>
+    pub addr : Addr<WsClient>,
+    pub rating : Option<i32>, //the joiner's rating , None without an account
+    pub password : Option<String>, //for private rooms , or to make a new room private
+    pub invite : Option<InvitedRoom>,
+    pub hints : bool, //only used when this join creates the room
+    pub bot : bool, //same , the computer takes the second seat
+    pub game : GameKind, //same , which game the new room plays
+    pub rules : RuleSet, //same , invite rooms play whatever the db record says
+    pub players : Option<usize>, //same , how many seats a multiplayer room has
+    pub teams : Option<TeamPlay> //same , two players share every mark
 }
 
 #[derive(Message)]
@@ -26,65 +49,329 @@ pub struct LeaveRoom{
 pub struct PlayerMove{
     pub room_id:Uuid,
     pub user_id : Uuid,
-    pub position : usize  //which cell to mark
+    pub mv : MovePayload  //the move in the room game's format , {"position":4} for tic-tac-toe
+}
+
+//a consult team member accepts (or turns down) the move a teammate proposed
+#[derive(Message)]
+#[rtype(result = "Result<(),String>")]
+pub struct AnswerProposal{
+    pub room_id:Uuid,
+    pub user_id:Uuid,
+    pub accept:bool
+}
+
+//chat to the whole room , or only to the sender's team
+#[derive(Message)]
+#[rtype(result = "Result<(),String>")]
+pub struct RoomChat{
+    pub room_id:Uuid,
+    pub user_id:Uuid,
+    pub text:String,
+    pub team:bool
+}
+
+//give up the game , in team rooms for the whole team
+#[derive(Message)]
+#[rtype(result = "Result<(),String>")]
+pub struct Resign{
+    pub room_id:Uuid,
+    pub user_id:Uuid
+}
+
+//best moves for the player whose turn it is , only in rooms with hints on
+#[derive(Message)]
+#[rtype(result = "Result<Analysis,String>")]
+pub struct RequestHint{
+    pub room_id:Uuid,
+    pub user_id:Uuid
+}
+
+//client wants the live list of open rooms
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SubscribeLobby{
+    pub user_id:Uuid,
+    pub addr:Addr<WsClient>
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct UnsubscribeLobby{
+    pub user_id:Uuid
 }
 
 pub struct RoomManager{
     pub rooms:HashMap<Uuid,Room>, //map of roomId ->Room 
-    pub user_room : HashMap<Uuid,Uuid>  //quick find which room a user is in
+    pub user_room : HashMap<Uuid,Uuid>,  //quick find which room a user is in
+    pub lobby : HashMap<Uuid,Addr<WsClient>>, //lobby subscribers
+    pub open_rooms : HashSet<Uuid>, //rooms currently listed in the lobby
+    pub events : Addr<EventLog>, //every room change is appended to the game_events log
+    pub solver : Arc<Mutex<Solver>> //shared by all hint requests so positions are only solved once , used on the blocking pool
 }
 
+//games with activity this recent are put back when the server starts
+const RESTORE_WINDOW_HOURS: i64 = 24;
+//how hard the bot thinks about each move
+const BOT_ITERATIONS: u32 = 20_000;
+const BOT_THINK_TIME: Duration = Duration::from_millis(500);
+//longest chat message , in characters
+const MAX_CHAT_LEN: usize = 500;
 
 impl RoomManager{
-    pub fn new()->Self{
+    pub fn new(events:Addr<EventLog>)->Self{
         Self { 
             rooms:HashMap::new(),
-            user_room:HashMap::new()
+            user_room:HashMap::new(),
+            lobby:HashMap::new(),
+            open_rooms:HashSet::new(),
+            events,
+            solver:Arc::new(Mutex::new(Solver::new()))
+        }
+    }
+
+    //put back the games that were going when the server stopped , before the actor starts.
+    //their players find their seats waiting when they reconnect (see Room::restore)
+    pub async fn restore_rooms(&mut self, db:&AnyDb){
+        let since = Utc::now() - chrono::Duration::hours(RESTORE_WINDOW_HOURS);
+        let room_ids = match db.get_active_rooms(since).await {
+            Ok(room_ids) => room_ids,
+            Err(e) => {
+                log::error!("Failed to load rooms to restore: {}", e);
+                return;
+            }
+        };
+        for room_id in room_ids {
+            let events:Vec<GameEvent> = match db.get_events(room_id).await {
+                Ok(events) => events.into_iter().map(|e|e.data).collect(),
+                Err(e) => {
+                    log::error!("Failed to load events for room {}: {}", room_id, e);
+                    continue;
+                }
+            };
+            match Room::restore(room_id, &events) {
+                Ok(Some(mut room)) => {
+                    //invite rooms carry on in step with their db row
+                    room.version = db.get_room_by_room_id(room_id).await.ok().map(|row|row.version);
+                    for user in room.players.iter().filter(|u|**u != Room::BOT_ID) {
+                        self.user_room.insert(*user, room_id);
+                    }
+                    self.rooms.insert(room_id, room);
+                }
+                Ok(None) => {}
+                Err(e) => log::error!("Failed to restore room {}: {}", room_id, e)
+            }
+        }
+        log::info!("Restored {} rooms with games in progress", self.rooms.len());
+    }
+
+    //call this after every change to a room so lobby subscribers see it
+    //sends lobby_room_opened / lobby_room_updated / lobby_room_closed depending on
+    //whether the room was listed before and whether it can still be joined now
+    fn publish_lobby(&mut self, room_id:Uuid){
+        let entry = self.rooms
+            .get(&room_id)
+            .filter(|room|room.is_open())
+            .map(LobbyRoom::from_room);
+
+        let payload = match entry {
+            Some(entry) => {
+                let kind = if self.open_rooms.insert(room_id) {
+                    "lobby_room_opened"
+                } else {
+                    "lobby_room_updated"
+                };
+                serde_json::json!({
+                    "type":kind,
+                    "room":entry
+                })
+            }
+            None => {
+                if !self.open_rooms.remove(&room_id) {
+                    return; //was never listed, nothing to tell
+                }
+                serde_json::json!({
+                    "type":"lobby_room_closed",
+                    "room_id":room_id
+                })
+            }
+        }
+        .to_string();
+
+        for a in self.lobby.values(){
+            a.do_send(RoomMessage(payload.clone()));
+        }
+    }
+
+    //if the bot is to move , search off the actor thread and send the move back as a normal PlayerMove
+    fn schedule_bot_move(&self, room_id:Uuid, ctx:&mut Context<Self>){
+        let Some(room) = self.rooms.get(&room_id) else {
+            return;
+        };
+        if !room.bot_to_move() {
+            return;
+        }
+        let game = room.game.clone_box();
+        let addr = ctx.address();
+        actix::spawn(async move {
+            let config = MctsConfig{
+                iterations:Some(BOT_ITERATIONS),
+                time_budget:Some(BOT_THINK_TIME),
+                ..Default::default()
+            };
+            match actix_web::rt::task::spawn_blocking(move || game.search_move(&config)).await {
+                Ok(best) => if let Some(mv) = best {
+                    addr.do_send(PlayerMove{ room_id, user_id:Room::BOT_ID, mv });
+                },
+                Err(e) => log::error!("Bot search failed in room {}: {}", room_id, e)
+            }
+        });
+    }
+
+    pub fn lobby_snapshot(&self)->Vec<LobbyRoom>{
+        self.open_rooms
+            .iter()
+            .filter_map(|id|self.rooms.get(id))
+            .map(LobbyRoom::from_room)
+            .collect()
+    }
+}
+
+
+//the shared solver , a search that panicked can't leave the table half-written so keep going
+fn lock(solver:&Mutex<Solver>)->MutexGuard<'_,Solver>{
+    solver.lock().unwrap_or_else(|e|e.into_inner())
+}
+
+//send `payload` to whichever of `users` are connected
+fn send_to(room:&Room, users:&[Uuid], payload:&str){
+    for uid in users {
+        if let Some(a) = room.addrs.get(uid) {
+            a.do_send(RoomMessage(payload.to_string()));
         }
     }
 }
 
+//`seat` gives up (its player resigned , or the last of its players left a game in progress) ,
+//logged as resigned so a replay folds the log the same way
+fn forfeit_seat(room:&mut Room, events:&Addr<EventLog>, user_id:Uuid, seat:usize)->Result<(),String>{
+    let mark = Mark::from_seat(seat).ok_or_else(||"user has no mark".to_string())?;
+    room.game.forfeit(seat)?;
+    events.do_send(RecordEvent{ room_id:room.id, event:GameEvent::Resigned{ user_id, mark } });
+    //an invite room's row ends with it , or the invite would let the game be picked up again
+    if let Some(version) = room.version.as_mut()
+        && !room.game.is_playing()
+    {
+        *version += 1;
+        events.do_send(FinishRoom{ room_id:room.id, winner:room.game.winner() });
+    }
+    Ok(())
+}
+
+//a message about `room` for `user` , `fields` go next to the game state
+fn room_message(room:&Room, user:&Uuid, fields:serde_json::Value)->String{
+    let mut message = room.state_message(user);
+    if let serde_json::Value::Object(fields) = fields {
+        message.extend(fields);
+    }
+    serde_json::Value::Object(message).to_string()
+}
 
 impl Actor for RoomManager{
     type Context = Context<Self>;
     fn started(&mut self, ctx: &mut Self::Context) {
         log::info!("Room manger actor statrted");
+        //a restored game may be waiting on the bot
+        let room_ids:Vec<Uuid> = self.rooms.keys().copied().collect();
+        for room_id in room_ids {
+            self.schedule_bot_move(room_id, ctx);
+        }
+    }
+}
+
+//what a join has to do with a room password before it goes ahead
+enum PasswordCheck{
+    Open, //no password involved , a reconnect , or an invite (checked when it was resolved)
+    Verify{ hash:String, given:Option<String> }, //the room has one , the joiner must know it
+    Hash(String) //a new private room , store the hash instead of the password
+}
+
+impl RoomManager{
+    fn password_check(&self, msg:&JoinRoom)->PasswordCheck{
+        if self.user_room.contains_key(&msg.user_id) {
+            return PasswordCheck::Open;
+        }
+        let hash = match (&msg.invite, msg.room_id) {
+            (Some(_), _) => return PasswordCheck::Open, //checked with the invite , see ws_client::resolve_invite
+            (None, Some(rid)) => self.rooms.get(&rid).and_then(|room|room.password.clone()),
+            (None, None) => return msg.password.clone().map_or(PasswordCheck::Open, PasswordCheck::Hash)
+        };
+        match hash {
+            Some(hash) => PasswordCheck::Verify{ hash, given:msg.password.clone() },
+            None => PasswordCheck::Open
+        }
     }
 }
 
 //handlet for join room message 
+//argon2 is slow on purpose , room passwords are hashed and checked on the blocking pool
+//so the other rooms don't wait , the join itself goes on back in the actor
 impl Handler<JoinRoom> for RoomManager{
-    type Result = Result<Uuid,String>;
+    type Result = ResponseActFuture<Self, Result<Uuid,String>>;
     fn handle(&mut self, msg: JoinRoom, _: &mut Context<Self>) -> Self::Result {
-        // CASE 1: User is already in a room (reconnection scenario)
-        if let Some(&existing_room_id) = self.user_room.get(&msg.user_id) {
-            if let Some(room) = self.rooms.get_mut(&existing_room_id) {
-                
-                room.addrs.insert(msg.user_id, msg.addr.clone());
-                
-                let mark = room.mark_for(&msg.user_id).unwrap_or('X');
-                let payload = serde_json::json!({
-                    "type": "rejoined",
-                    "room_id": room.id.to_string(),
-                    "board": room.game.board,
-                    "your_mark": mark.to_string(),
-                    "turn": room.game.turn.to_string(),
-                    "status": room.game.status,
-                    "winner": room.game.winner,
-                    "players": room.players.len(),
-                })
-                .to_string();
-
-                if let Some(a) = room.addrs.get(&msg.user_id) {
-                    let _ = a.do_send(RoomMessage(payload));
+        let check = self.password_check(&msg);
+        Box::pin(
+            async move {
+                match check {
+                    PasswordCheck::Open => Ok(None),
+                    PasswordCheck::Verify{ given:None, .. } => Err("incorrect room password".to_string()),
+                    PasswordCheck::Verify{ hash, given:Some(given) } => {
+                        match actix_web::rt::task::spawn_blocking(move || db::verify_password(&given, &hash)).await {
+                            Ok(true) => Ok(None),
+                            Ok(false) => Err("incorrect room password".to_string()),
+                            Err(_) => Err("internal server error".to_string())
+                        }
+                    }
+                    PasswordCheck::Hash(password) => {
+                        match actix_web::rt::task::spawn_blocking(move || db::hash_password(&password)).await {
+                            Ok(Ok(hash)) => Ok(Some(hash)),
+                            _ => Err("internal server error".to_string())
+                        }
+                    }
                 }
-
-                return Ok(existing_room_id);
             }
+            .into_actor(self)
+            .map(move |checked, act, ctx| act.join(msg, checked?, ctx))
+        )
+    }
+}
+
+impl RoomManager{
+    //`new_hash` is the hashed password when this join creates a private room
+    fn join(&mut self, msg:JoinRoom, new_hash:Option<String>, ctx:&mut Context<Self>)->Result<Uuid,String>{
+        // CASE 1: User is already in a room (reconnection scenario)
+        if let Some(&existing_room_id) = self.user_room.get(&msg.user_id)
+            && let Some(room) = self.rooms.get_mut(&existing_room_id) {
+            
+            room.addrs.insert(msg.user_id, msg.addr.clone());
             
+            let mark = room.mark_for(&msg.user_id).unwrap_or(Mark::X);
+            let payload = room_message(room, &msg.user_id, serde_json::json!({
+                "type": "rejoined",
+                "your_mark": mark.to_string(),
+                "players": room.players.len(),
+            }));
+
+            if let Some(a) = room.addrs.get(&msg.user_id) {
+                a.do_send(RoomMessage(payload));
+            }
+
+            return Ok(existing_room_id);
         }
         //Case-2 join a specific room or create a new one
-        let room_id = if let Some(rid) = msg.room_id{
+        let room_id = if let Some(invite) = &msg.invite {
+            invite.room_id
+        } else if let Some(rid) = msg.room_id{
             if !self.rooms.contains_key(&rid){
                 return Err("room not found".into());
             }
@@ -92,53 +379,101 @@ impl Handler<JoinRoom> for RoomManager{
         }else {
             Uuid::new_v4()
         };
+        let is_new = !self.rooms.contains_key(&room_id);
 
         //get or create a room 
-        let room = self.rooms.entry(room_id)
-            .or_insert_with(||{    //use of clousers =>create a value only if key does not exist
-            log::info!("Created new room: {}", room_id);
-            Room::new(room_id)  //without clouser its excute everytime ,event if room exist in hashmap ,Rust only calls the closure when the key is missing.
-        });
+        let room = match self.rooms.entry(room_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut room = if let Some(invite) = &msg.invite {
+                    //the first player arriving through an invite sets up the room from the db record
+                    if !invite.fresh {
+                        return Err("the game in this room can't be picked up again".into());
+                    }
+                    log::info!("Created private room from invite: {}", room_id);
+                    let mut room = Room::private(room_id, invite.game.new_game(invite.rules, None)?, invite.password.clone());
+                    room.version = Some(invite.version);
+                    room
+                } else {
+                    //rules the game doesn't have are turned down before the room exists
+                    if msg.teams.is_some() && msg.bot {
+                        return Err("the bot doesn't play team games".into());
+                    }
+                    let game = msg.game.new_game(msg.rules, msg.players)?;
+                    log::info!("Created new room: {}", room_id);
+                    match new_hash {  //whoever creates a room with a password makes it private
+                        Some(hash) => Room::private(room_id, game, Some(hash)),
+                        None => Room::new(room_id, game)
+                    }
+                };
+                room.hints = msg.hints;
+                if msg.invite.is_none() {
+                    room.teams = msg.teams;
+                }
+                entry.insert(room)
+            }
+        };
 
-        if room.is_full() {
+        //sit the player down , the seat stays theirs even if somebody else leaves.
+        //invite rooms seat everybody where the row has them (the O seat was filled in the row before the join)
+        let seat = match &msg.invite {
+            Some(invite) => {
+                room.version = room.version.max(Some(invite.version));
+                room.sit_in(msg.user_id, if msg.user_id == invite.player_x_id { 0 } else { 1 })
+            }
+            None => room.sit(msg.user_id)
+        };
+        let Some(seat) = seat else {
             return Err("room is full".into());
-        }
-        //push the player in room
-        room.players.push(msg.user_id);
+        };
         room.addrs.insert(msg.user_id,msg.addr.clone());
+        if let Some(rating) = msg.rating {
+            room.ratings.insert(msg.user_id, rating);
+        }
         self.user_room.insert(msg.user_id,room_id);
 
-        room.start_game_if_ready();
+        let mark = Mark::from_seat(seat).ok_or_else(||"user has no mark".to_string())?;
+        //only log the room once somebody actually got in
+        if is_new {
+            self.events.do_send(RecordEvent{ room_id, event:GameEvent::Created{
+                private:room.private,
+                hints:room.hints,
+                game:room.game.kind(),
+                rules:room.game.rules(),
+                players:(room.game.player_count() > 2).then_some(room.game.player_count()),
+                teams:room.teams
+            } });
+        }
+        self.events.do_send(RecordEvent{ room_id, event:GameEvent::Joined{ user_id:msg.user_id, mark } });
 
-        let mark = room.mark_for(&msg.user_id).unwrap();
+        //a new room asked for a computer opponent , it sits down right away (not for invites , those wait for a friend)
+        if is_new && msg.bot && msg.invite.is_none()
+            && let Some(bot_mark) = room.sit(Room::BOT_ID).and_then(Mark::from_seat)
+        {
+            self.events.do_send(RecordEvent{ room_id, event:GameEvent::Joined{ user_id:Room::BOT_ID, mark:bot_mark } });
+        }
+
+        room.start_game_if_ready();
 
-         let payload = serde_json::json!({
+         let payload = room_message(room, &msg.user_id, serde_json::json!({
             "type": "joined",
-            "room_id": room_id.to_string(),
-            "board": room.game.board,
             "your_mark": mark.to_string(),
-            "turn": room.game.turn.to_string(),
-            "status": room.game.status,
             "players": room.players.len(),
-        })
-        .to_string();
+            "hints": room.hints,
+            "bot": room.has_bot(),
+        }));
          
         if let Some(a) = room.addrs.get(&msg.user_id){
-            let _ = a.do_send(RoomMessage(payload.clone()));
+            a.do_send(RoomMessage(payload.clone()));
         }
          //Notify others player that someone joined
         for(uid,a) in room.addrs.iter(){
             if uid != &msg.user_id {
-                let other_payload = serde_json::json!({
+                let other_payload = room_message(room, uid, serde_json::json!({
                     "type":"player_joined",
-                    "room_id":room_id.to_string(),
-                    "board":room.game.board,
-                    "turn":room.game.turn.to_string(),
-                    "status":room.game.status,
                     "players":room.players.len()
-                })
-                .to_string();
-            let _ = a.do_send(RoomMessage(other_payload));
+                }));
+            a.do_send(RoomMessage(other_payload));
             }
         }
         log::info!(
@@ -147,6 +482,8 @@ impl Handler<JoinRoom> for RoomManager{
             room_id,
             room.players.len()
         );
+        self.publish_lobby(room_id);
+        self.schedule_bot_move(room_id, ctx);
         Ok(room_id)
     }
 }
@@ -155,100 +492,468 @@ impl Handler<JoinRoom> for RoomManager{
 impl Handler<LeaveRoom> for RoomManager{
     type Result = ();
     fn handle(&mut self, msg: LeaveRoom, ctx: &mut Self::Context) -> Self::Result {
-        
-        if let Some(room) = self.rooms.get_mut(&msg.room_id){
-            room.players.retain(|u|u != &msg.user_id);  //retain keeps only the elements for which the closure returns true.
-            room.addrs.remove(&msg.user_id);
-
-        
-            self.user_room.remove(&msg.user_id);
-
-            log::info!(
-                "player {} left from {} (remaining players:{}) ",
-                msg.user_id,
-                msg.room_id,
-                room.players.len()
-            );
-            //Notify this thing to others player
-
+        let Some(room) = self.rooms.get_mut(&msg.room_id) else {
+            return;
+        };
+        let seat = room.leave(&msg.user_id);
+        self.user_room.remove(&msg.user_id);
+        self.events.do_send(RecordEvent{ room_id:msg.room_id, event:GameEvent::Left{ user_id:msg.user_id } });
+
+        //a team move the leaver proposed (or could have confirmed) is dropped , the team picks again
+        if let Some(seat) = seat
+            && room.proposal.take_if(|p|p.seat == seat).is_some()
+        {
             let payload = serde_json::json!({
-                "type":"player-left",
-                "user_id":msg.user_id,
-                "room_id": msg.room_id,
-                "player":room.players.len()
+                "type":"move_rejected",
+                "room_id":msg.room_id,
+                "user_id":msg.user_id
             })
             .to_string();
-            
-            for(_,a) in room.addrs.iter(){
-                let _ = a.do_send(RoomMessage(payload.clone()));
-            }
+            send_to(room, &room.team(seat), &payload);
+        }
 
-            if room.players.is_empty(){
-                self.rooms.remove(&msg.room_id);
-                log::info!("")
-            }
+        //walking out of a game in progress gives it up once nobody is left playing that seat ,
+        //a teammate who is still there takes over its turns and the others keep their seats
+        //(multiplayer elimination plays on without them , every other game ends here)
+        let forfeit = seat.filter(|s|room.game.is_playing() && room.team(*s).is_empty());
+        if let Some(seat) = forfeit
+            && let Err(e) = forfeit_seat(room, &self.events, msg.user_id, seat)
+        {
+            log::error!("Failed to forfeit seat {} in room {}: {}", seat, msg.room_id, e);
+        }
+
+        log::info!(
+            "player {} left from {} (remaining players:{}) ",
+            msg.user_id,
+            msg.room_id,
+            room.players.len()
+        );
+        //Notify this thing to others player , with the game as it is now
+        for(uid,a) in room.addrs.iter(){
+            let payload = room_message(room, uid, serde_json::json!({
+                "type":"player-left",
+                "user_id":msg.user_id,
+                "player":room.players.len(),
+                "resigned":forfeit.is_some()
+            }));
+            a.do_send(RoomMessage(payload));
+        }
 
+        if forfeit.is_some() && !room.game.is_playing() {
+            self.game_over(msg.room_id);
+        }
+        if self.rooms.get(&msg.room_id).is_some_and(Room::only_bots){
+            self.rooms.remove(&msg.room_id);
+            log::info!("Closed room {} , nobody left", msg.room_id);
         }
+
+        self.publish_lobby(msg.room_id);
+        self.schedule_bot_move(msg.room_id, ctx);
+    }
+}
+
+impl Handler<Resign> for RoomManager{
+    type Result = Result<(),String>;
+    fn handle(&mut self, msg: Resign, ctx: &mut Self::Context) -> Self::Result {
+        let room = self.rooms
+            .get_mut(&msg.room_id)
+            .ok_or_else(||"room not found".to_string())?;
+
+        let seat = room
+            .seat_of(&msg.user_id)
+            .ok_or_else(||"user not in the room".to_string())?;
+        forfeit_seat(room, &self.events, msg.user_id, seat)?;
+        room.proposal.take_if(|p|p.seat == seat);
+
+        for(uid,a) in room.addrs.iter(){
+            let payload = room_message(room, uid, serde_json::json!({
+                "type":"player_resigned",
+                "user_id":msg.user_id,
+                "mark":Mark::from_seat(seat)
+            }));
+            a.do_send(RoomMessage(payload));
+        }
+        if !room.game.is_playing() {
+            self.game_over(msg.room_id);
+        }
+        self.schedule_bot_move(msg.room_id, ctx);
+        Ok(())
     }
 }
 
 //this is actix actor handler
 //ctx is actor's context - the runtime enviorment in which the actor is running
 impl Handler<PlayerMove> for RoomManager{
-    type Result = Result<(),String>;
+    type Result = AtomicResponse<Self, Result<(),String>>;
     fn handle(&mut self, msg: PlayerMove, ctx: &mut Self::Context) -> Self::Result {
+        //invite rooms play the move on their db row first , see row_move
+        if let Some(version) = self.rooms.get(&msg.room_id).and_then(|room|room.version) {
+            return self.row_move(msg, version);
+        }
+        AtomicResponse::new(Box::pin(actix::fut::ready(self.player_move(msg, ctx))))
+    }
+}
+
+impl RoomManager{
+    fn player_move(&mut self, msg: PlayerMove, ctx: &mut Context<Self>)->Result<(),String>{
         let room = self.rooms
             .get_mut(&msg.room_id)
             .ok_or_else(||"room not found".to_string())?;
 
-        if !room.players.contains(&msg.user_id){
-            return Err("user not in the room".into());
+        let seat = room
+            .seat_of(&msg.user_id)
+            .ok_or_else(||"user not in the room".to_string())?;
+
+        match room.teams {
+            //a consult team's move is only a proposal until a teammate confirms it
+            //(a player whose teammates all left plays on their own)
+            Some(TeamPlay::Consult) if room.team(seat).len() > 1 => {
+                if seat != room.game.to_move() {
+                    return Err("its not your turn buddy".into());
+                }
+                let proposed = room.game.clone_box().play(seat, &msg.mv)?; //checked on a copy , nothing is played yet
+                room.proposal = Some(Proposal{ user_id:msg.user_id, seat, mv:msg.mv });
+                let payload = serde_json::json!({
+                    "type":"move_proposed",
+                    "room_id":msg.room_id,
+                    "user_id":msg.user_id,
+                    "move":proposed
+                })
+                .to_string();
+                send_to(room, &room.team(seat), &payload);
+                return Ok(());
+            }
+            Some(TeamPlay::Alternate) if seat == room.game.to_move() && room.next_player() != Some(msg.user_id) => {
+                return Err("its your teammate's turn".into());
+            }
+            _ => {}
         }
+        self.play_move(msg.room_id, msg.user_id, msg.mv, ctx)
+    }
+}
 
-        let mark = room
-            .mark_for(&msg.user_id)
+impl Handler<AnswerProposal> for RoomManager{
+    type Result = Result<(),String>;
+    fn handle(&mut self, msg: AnswerProposal, ctx: &mut Self::Context) -> Self::Result {
+        let room = self.rooms
+            .get_mut(&msg.room_id)
+            .ok_or_else(||"room not found".to_string())?;
+
+        let seat = room
+            .seat_of(&msg.user_id)
+            .ok_or_else(||"user not in the room".to_string())?;
+        let Some(proposal) = room.proposal.take_if(|p|p.seat == seat) else {
+            return Err("your team has no move to confirm".into());
+        };
+        if msg.accept && proposal.user_id == msg.user_id {
+            room.proposal = Some(proposal);
+            return Err("a teammate has to confirm your move".into());
+        }
+
+        if !msg.accept {
+            //the proposer can take it back too , either way the team picks again
+            let payload = serde_json::json!({
+                "type":"move_rejected",
+                "room_id":msg.room_id,
+                "user_id":msg.user_id
+            })
+            .to_string();
+            send_to(room, &room.team(seat), &payload);
+            return Ok(());
+        }
+        self.play_move(msg.room_id, proposal.user_id, proposal.mv, ctx)
+    }
+}
+
+impl Handler<RoomChat> for RoomManager{
+    type Result = Result<(),String>;
+    fn handle(&mut self, msg: RoomChat, _: &mut Self::Context) -> Self::Result {
+        let room = self.rooms
+            .get(&msg.room_id)
+            .ok_or_else(||"room not found".to_string())?;
+
+        let seat = room
+            .seat_of(&msg.user_id)
+            .ok_or_else(||"user not in the room".to_string())?;
+        let text = msg.text.trim();
+        if text.is_empty() {
+            return Err("empty message".into());
+        }
+        if text.chars().count() > MAX_CHAT_LEN {
+            return Err(format!("messages are at most {} characters", MAX_CHAT_LEN));
+        }
+
+        let recipients = if msg.team {
+            if room.teams.is_none() {
+                return Err("this room has no teams".into());
+            }
+            room.team(seat)
+        } else {
+            room.players.clone()
+        };
+        let payload = serde_json::json!({
+            "type":"chat",
+            "room_id":msg.room_id,
+            "user_id":msg.user_id,
+            "text":text,
+            "team":msg.team
+        })
+        .to_string();
+        send_to(room, &recipients, &payload);
+
+        //team chat is private , only the room chat goes in the log (and the replays)
+        if !msg.team {
+            self.events.do_send(RecordEvent{ room_id:msg.room_id, event:GameEvent::Chat{ user_id:msg.user_id, text:text.to_string() } });
+        }
+        Ok(())
+    }
+}
+
+impl RoomManager{
+    //a move in an invite room: checked on a copy , written to the db row (which checks it again against
+    //the stored board and version) and only played here once it is in. the actor waits for the write
+    //so nothing else happens to the room in between , invite rooms have no teams and no bot
+    fn row_move(&mut self, msg:PlayerMove, version:i32)->AtomicResponse<Self,Result<(),String>>{
+        let checked = self.rooms
+            .get(&msg.room_id)
+            .ok_or_else(||"room not found".to_string())
+            .and_then(|room|{
+                let seat = room
+                    .seat_of(&msg.user_id)
+                    .ok_or_else(||"user not in the room".to_string())?;
+                room.game.clone_box().play(seat, &msg.mv)?;
+                db::models::move_position(room.game.kind(), &msg.mv).map_err(|e|e.to_string())
+            });
+        let position = match checked {
+            Ok(position) => position,
+            Err(e) => return AtomicResponse::new(Box::pin(actix::fut::ready(Err(e))))
+        };
+        let apply = ApplyMove{ room_id:msg.room_id, user_id:msg.user_id, position, expected_version:version };
+        AtomicResponse::new(Box::pin(
+            self.events
+                .send(apply)
+                .into_actor(self)
+                .map(move |written, act, ctx| match written {
+                    Ok(Ok(row)) => {
+                        if let Some(room) = act.rooms.get_mut(&msg.room_id) {
+                            room.version = Some(row.version);
+                        }
+                        act.play_move(msg.room_id, msg.user_id, msg.mv, ctx)
+                    }
+                    Ok(Err(MoveRejected::Invalid(e))) => Err(e),
+                    Ok(Err(MoveRejected::Stale{ row, moves })) => {
+                        let conflict = format!("the game changed before your move went in (version {} , expected {}) , look at the board again", row.version, version);
+                        act.resync(row, &moves);
+                        Err(conflict)
+                    }
+                    Err(_) => Err("internal server error".into())
+                })
+        ))
+    }
+
+    //the row moved on without the room (another server , or somebody writing to the db) ,
+    //the row wins: take its game over and show everybody the board as it is now
+    fn resync(&mut self, row:RoomRow, moves:&[Move]){
+        let Some(room) = self.rooms.get_mut(&row.id) else {
+            return;
+        };
+        match row.room_game() {
+            Ok(game) => room.game = game,
+            Err(e) => {
+                log::error!("Failed to resync room {}: {}", row.id, e);
+                return;
+            }
+        }
+        room.moves = moves.iter().map(|m|m.payload(row.game)).collect();
+        room.version = Some(row.version);
+        log::warn!("Room {} fell behind its db row , resynced to version {}", row.id, row.version);
+        for(uid,a) in room.addrs.iter(){
+            let payload = room_message(room, uid, serde_json::json!({
+                "type":"resynced"
+            }));
+            a.do_send(RoomMessage(payload));
+        }
+    }
+
+    //play a move that passed the room's checks (the team ones included) for `user_id`
+    fn play_move(&mut self, room_id:Uuid, user_id:Uuid, mv:MovePayload, ctx:&mut Context<Self>)->Result<(),String>{
+        let room = self.rooms
+            .get_mut(&room_id)
+            .ok_or_else(||"room not found".to_string())?;
+
+        let seat = room
+            .seat_of(&user_id)
+            .ok_or_else(||"user not in the room".to_string())?;
+        let mark = Mark::from_seat(seat)
             .ok_or_else(||"user has no mark".to_string())?;
 
-        room.game.apply_move(msg.position,mark);
+        let played = room.game.play(seat, &mv)?;
+        room.moved(seat);
+        room.moves.push(played.clone());
+        //invite rooms had the move logged by apply_move , together with the row
+        if room.version.is_none() {
+            self.events.do_send(RecordEvent{
+                room_id,
+                event:GameEvent::Moved{ user_id, mark, mv:played.clone() }
+            });
+        }
 
         log::info!(
-            "Player {} ({}) moved to position {} in room {}",
-            msg.user_id,
+            "Player {} ({}) played {} in room {}",
+            user_id,
             mark,
-            msg.position,
+            serde_json::Value::Object(played.clone()),
             room.id
         );
 
-        //build updated payload of game state
-        let payload = serde_json::json!({
-            "payload":"palyer_moved",
-            "room_id":msg.room_id,
-            "board":room.game.board,
-            "turn":room.game.turn,
-            "status":room.game.status,
-            "winner":room.game.winner,
-            "last_move":{
-                "position":msg.position,
-                "mark":mark.to_string()
-            }
-        })
-        .to_string();
+        //build updated payload of game state , every player gets their own view of the board
+        let mut last_move = played;
+        last_move.insert("mark".into(), mark.to_string().into());
+        //in fog of war the others only learn that a move was made , not where
+        let mut hidden_move = serde_json::Map::new();
+        hidden_move.insert("mark".into(), mark.to_string().into());
 
-        for(_,a) in room.addrs.iter(){
-            let _ = a.do_send(RoomMessage( payload.clone()));
+        for(uid,a) in room.addrs.iter(){
+            let seen = if room.seat_of(uid) == Some(seat) || !room.hides_moves() { &last_move } else { &hidden_move };
+            let payload = room_message(room, uid, serde_json::json!({
+                "payload":"palyer_moved",
+                "last_move":seen
+            }));
+            a.do_send(RoomMessage(payload));
         }
-        if room.game.status != "playing" {
-            log::info!(
-                "Game ended in room {}: {}",
-                room.id,
-                if let Some(w) = room.game.winner {
-                    format!("Winner: {}", w)
-                } else {
-                    "Draw".to_string()
-                }
-            );
+        if !room.game.is_playing() {
+            self.game_over(room_id);
         }
+        self.schedule_bot_move(room_id, ctx);
         Ok(())
     }
+
+    //the game in `room_id` just ended (a move or a player walking out) , review and rate it
+    fn game_over(&mut self, room_id:Uuid){
+        let Some(room) = self.rooms.get(&room_id) else {
+            return;
+        };
+        //grade the finished game for both players , the solver only knows the 3x3 board.
+        //the review runs on the blocking pool and goes to whoever was in the room when the game ended
+        if room.game.as_any().is::<GameState>() {
+            let rules = room.game.rules();
+            let moves = room.moves.clone();
+            let players = room.players.clone();
+            let addrs:Vec<Addr<WsClient>> = room.addrs.values().cloned().collect();
+            let solver = self.solver.clone();
+            actix::spawn(async move {
+                let review = actix_web::rt::task::spawn_blocking(move || {
+                    moves
+                        .iter()
+                        .map(decode_payload::<GameState>)
+                        .collect::<Result<Vec<CellMove>,String>>()
+                        .and_then(|moves|lock(&solver).review(rules, &moves))
+                })
+                .await
+                .unwrap_or_else(|e|Err(e.to_string()));
+                match review {
+                    Ok(review) => {
+                        let payload = serde_json::json!({
+                            "type":"game_review",
+                            "room_id":room_id,
+                            "players":players,
+                            "review":review
+                        })
+                        .to_string();
+                        for a in addrs {
+                            a.do_send(RoomMessage(payload.clone()));
+                        }
+                    }
+                    Err(e) => log::error!("Failed to review game in room {}: {}", room_id, e)
+                }
+            });
+        }
+        //games against the bot aren't rated , a team is rated as one player on its average rating
+        //and players who walked out are rated on the seat they gave up
+        if !room.has_bot()
+            && room.is_full()
+            && let Some(places) = room.game.places()
+        {
+            let seats = (0..places.len()).map(|s|room.seated(s)).collect();
+            self.events.do_send(RateGame{ room_id, seats, places });
+        }
+        log::info!(
+            "Game ended in room {}: {}",
+            room.id,
+            match room.game.winner() {
+                Some(w) => format!("Winner: {}", w),
+                None => "Draw".to_string()
+            }
+        );
+    }
+}
+
+//the position is checked here , the search itself runs on the blocking pool so a slow
+//solve doesn't hold up every other room
+impl Handler<RequestHint> for RoomManager{
+    type Result = ResponseActFuture<Self, Result<Analysis,String>>;
+    fn handle(&mut self, msg: RequestHint, _: &mut Self::Context) -> Self::Result {
+        let checked = self.hint_position(&msg);
+        let solver = self.solver.clone();
+        Box::pin(
+            async move {
+                let game = checked?;
+                actix_web::rt::task::spawn_blocking(move || lock(&solver).analyze(&game))
+                    .await
+                    .map_err(|_|"internal server error".to_string())
+            }
+            .into_actor(self)
+        )
+    }
+}
+
+impl RoomManager{
+    //the board to hint on , when `msg.user_id` may ask for one right now
+    fn hint_position(&self, msg: &RequestHint)->Result<GameState,String>{
+        let room = self.rooms
+            .get(&msg.room_id)
+            .ok_or_else(||"room not found".to_string())?;
+
+        if !room.hints {
+            return Err("hints are off in this room".into());
+        }
+        let game = room.game
+            .as_any()
+            .downcast_ref::<GameState>()
+            .ok_or_else(||format!("hints are not available for {} {}", room.game.kind(), room.game.rules()))?;
+        let mark = room
+            .mark_for(&msg.user_id)
+            .ok_or_else(||"user not in the room".to_string())?;
+        if !game.is_playing() {
+            return Err("game is not in progress".into());
+        }
+        if game.turn != mark {
+            return Err("its not your turn".into());
+        }
+        Ok(game.clone())
+    }
+}
+
+impl Handler<SubscribeLobby> for RoomManager{
+    type Result = ();
+    fn handle(&mut self, msg: SubscribeLobby, _: &mut Self::Context) -> Self::Result {
+        //first give the new subscriber everything that is open right now ,
+        //after that it only receives the opened/updated/closed events
+        let payload = serde_json::json!({
+            "type":"lobby",
+            "rooms":self.lobby_snapshot()
+        })
+        .to_string();
+        msg.addr.do_send(RoomMessage(payload));
+
+        self.lobby.insert(msg.user_id, msg.addr);
+        log::info!("User {} subscribed to lobby (subscribers: {})", msg.user_id, self.lobby.len());
+    }
 }
 
+impl Handler<UnsubscribeLobby> for RoomManager{
+    type Result = ();
+    fn handle(&mut self, msg: UnsubscribeLobby, _: &mut Self::Context) -> Self::Result {
+        self.lobby.remove(&msg.user_id);
+    }
+}
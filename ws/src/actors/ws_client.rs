@@ -3,9 +3,17 @@ use actix_web_actors::ws;
 use serde::Deserialize;
 use std::{time::{Duration, Instant}};
 use uuid::Uuid;
+use db::{AnyDb, EventRepo, InviteRepo, RoomRepo, UserRepo, models::GameReplay};
+use app_config::WsConfig;
+use game::{GameKind, MovePayload, QuantumMove, QuantumState, RuleSet, TeamPlay, encode_payload};
 
 
-use crate::{ JoinRoom, LeaveRoom, PlayerMove, RoomManager};
+use crate::{ AnswerProposal, InvitedRoom, JoinRoom, LeaveRoom, PlayerMove, RequestHint, Resign, RoomChat, RoomManager, SubscribeLobby, UnsubscribeLobby};
+
+//longest pause between two replay frames , whatever the speed
+const MAX_REPLAY_DELAY: Duration = Duration::from_secs(3);
+//allowed range for the watch_replay speed factor (1.0 = real time)
+const REPLAY_SPEEDS: std::ops::RangeInclusive<f64> = 0.25..=16.0;
 
 // Message sent from RoomManager to WsClient
 //Contains a JSON string to be sent to the WebSocket client
@@ -18,27 +26,37 @@ pub struct RoomMessage(pub String);
 pub  struct WsClient {
     pub user_id : Uuid,
     pub room_mgr : Addr<RoomManager>,
+    pub db : AnyDb,
     pub current_room : Option<Uuid>,
-    hb:Instant
+    pub in_lobby : bool, //subscribed to the open-room feed
+    replay_timers : Vec<SpawnHandle>, //pending frames of the replay being watched
+    hb:Instant,
+    hb_interval:Duration, //how often we ping
+    client_timeout:Duration //how long we wait for a pong before giving up
 }
 
 impl WsClient {
-    pub fn new(user_id:Uuid,room_mgr:Addr<RoomManager>)->Self{
+    pub fn new(user_id:Uuid,room_mgr:Addr<RoomManager>,db:AnyDb,config:&WsConfig)->Self{
         Self { 
             user_id,
             room_mgr,
+            db,
             current_room:None,
-            hb:Instant::now()
+            in_lobby:false,
+            replay_timers:Vec::new(),
+            hb:Instant::now(),
+            hb_interval:config.heartbeat_interval(),
+            client_timeout:config.client_timeout()
          }
     }
-    //send pind every 5 second and if client reponds 
+    //send pind every hb_interval (5 second by default) and if client reponds 
     //ctx allows scheduling timers, sending ping, stopping actor, etc.
     fn hb(&self, ctx: &mut ws::WebsocketContext<Self>) {
-        //Every 5 seconds, Actix will call the closure.
+        //Every hb_interval, Actix will call the closure.
         //act muttable ref to the actor and ctx is actor context to send message or stop the actor
-        ctx.run_interval(Duration::from_secs(5), |act, ctx| {//So every 5 seconds this block executes:
-            // Check if we've received a pong within the last 10 seconds
-            if Instant::now().duration_since(act.hb) > Duration::from_secs(10) {
+        ctx.run_interval(self.hb_interval, |act, ctx| {//So every hb_interval this block executes:
+            // Check if we've received a pong within the client timeout (10 seconds by default)
+            if Instant::now().duration_since(act.hb) > act.client_timeout {
                 // No response - connection is dead
                 log::warn!("WebSocket heartbeat failed, disconnecting user {}", act.user_id);
                 ctx.stop();  // Stop this actor (triggers cleanup)
@@ -49,12 +67,120 @@ impl WsClient {
         });
     }
 
+    //hand a move to the room manager , only errors come back here (the new state is broadcast to the room)
+    fn send_move(&self, room_id:&str, mv:MovePayload, ctx: &mut ws::WebsocketContext<Self>){
+        let user_id = self.user_id;
+        self.send_to_room(room_id, |room_id| PlayerMove{ room_id, user_id, mv }, ctx);
+    }
+
+    //same for anything else the room answers with Ok or an error , `msg` gets the parsed room id
+    fn send_to_room<M>(&self, room_id:&str, msg:impl FnOnce(Uuid)->M, ctx: &mut ws::WebsocketContext<Self>)
+    where
+        M: Message<Result = Result<(),String>> + Send + 'static,
+        RoomManager: Handler<M>,
+    {
+        if let Ok(room_uuid) = Uuid::parse_str(room_id){
+            let msg = msg(room_uuid);
+            let mgr = self.room_mgr.clone();
+
+            async move {
+                mgr.send(msg).await
+            }
+            .into_actor(self)
+            .then(move |result,_,ctx| {
+                if let Ok(Err(e)) = result {
+                    let err = serde_json::json!({
+                        "type": "error",
+                        "message": e
+                    })
+                    .to_string();
+                    ctx.text(err);
+                }
+                fut::ready(())
+            })
+            .spawn(ctx);
+
+        }else {
+            // Invalid room ID format
+            let err = serde_json::json!({
+                "type": "error",
+                "message": "invalid room id"
+            })
+            .to_string();
+            ctx.text(err);
+        }
+    }
+
+    //drop the frames of a replay that is still playing
+    fn stop_replay(&mut self, ctx: &mut ws::WebsocketContext<Self>){
+        for handle in self.replay_timers.drain(..){
+            ctx.cancel_future(handle);
+        }
+    }
+
+    //schedule every move of the replay , each one waits for the time the player
+    //took (divided by speed , capped so long pauses don't stall the viewer)
+    fn play_replay(&mut self, replay:GameReplay, speed:f64, ctx: &mut ws::WebsocketContext<Self>){
+        self.stop_replay(ctx);
+
+        let start = serde_json::json!({
+            "type":"replay_start",
+            "room_id":replay.room_id,
+            "players":replay.players,
+            "moves":replay.moves.len(),
+            "speed":speed
+        })
+        .to_string();
+        ctx.text(start);
+
+        let end = serde_json::json!({
+            "type":"replay_end",
+            "room_id":replay.room_id,
+            "status":replay.status,
+            "winner":replay.winner
+        })
+        .to_string();
+
+        let mut at = Duration::ZERO;
+        let mut frames = vec![];
+        for mv in &replay.moves {
+            let wait = Duration::from_millis(mv.think_ms.max(0) as u64).div_f64(speed);
+            at += wait.min(MAX_REPLAY_DELAY);
+            let frame = serde_json::json!({
+                "type":"replay_move",
+                "room_id":replay.room_id,
+                "move":mv
+            })
+            .to_string();
+            frames.push((at, frame));
+        }
+
+        if frames.is_empty() {
+            ctx.text(end);
+            return;
+        }
+        //replay_end goes out with the last move so it can't overtake it
+        let last = frames.len() - 1;
+        let mut end = Some(end);
+        for (i, (at, frame)) in frames.into_iter().enumerate() {
+            let end = if i == last { end.take() } else { None };
+            let handle = ctx.run_later(at, move |act, ctx| {
+                ctx.text(frame);
+                if let Some(end) = end {
+                    act.replay_timers.clear();
+                    ctx.text(end);
+                }
+            });
+            self.replay_timers.push(handle);
+        }
+    }
+
 }
 
 impl Actor for WsClient{
     type Context = ws::WebsocketContext<Self>;
-    /// Called when the actor starts (WebSocket connection established)
 
+    /// Called when the actor starts (WebSocket connection established)
     fn started(&mut self, ctx: &mut Self::Context) {
         log::info!("ws client started for user:{}",self.user_id);
         self.hb(ctx);
@@ -69,7 +195,7 @@ impl Actor for WsClient{
         ctx.text(welcome_payload);
     }
     //called when actor us stoping
-    fn stopping(&mut self, ctx: &mut Self::Context) -> Running {
+    fn stopping(&mut self, _: &mut Self::Context) -> Running {
         log::info!("ws client stoppinf for user:{}",self.user_id);
 
         if let Some(room_id) = self.current_room{
@@ -79,6 +205,9 @@ impl Actor for WsClient{
             };
             self.room_mgr.do_send(leave);
         }
+        if self.in_lobby{
+            self.room_mgr.do_send(UnsubscribeLobby{ user_id:self.user_id });
+        }
         Running::Stop 
     }
 }
@@ -97,17 +226,73 @@ impl Handler<RoomMessage> for WsClient {
 //Serde will automatically parse JSON into the correct enum variant based on "type" field
 
 #[derive(Deserialize)] //Serde will look at the JSON field "type" and use it to determine which enum variant to pick.
-#[serde(tag = "type", rename_all = "lowercase")]
+#[serde(tag = "type", rename_all = "snake_case")]
 enum ClientCmd{
     Join{
-        room_id : Option<String>
+        room_id : Option<String>,
+        #[serde(default)]
+        invite_code : Option<String>,
+        #[serde(default)]
+        password : Option<String>,
+        #[serde(default)]
+        hints : bool, //a new room lets players ask for hints
+        #[serde(default)]
+        bot : bool, //a new room gets a computer opponent
+        #[serde(default)]
+        game : GameKind, //what a new room plays , tic_tac_toe , connect_four , qubic or multiplayer
+        #[serde(default)]
+        rules : RuleSet, //how a new room plays , standard , misere , wild , notakto , order_and_chaos , quantum , fog , fog_retry or elimination
+        #[serde(default)]
+        players : Option<usize>, //seats in a new multiplayer room , 3 or 4
+        #[serde(default)]
+        teams : Option<TeamPlay> //a new team room , alternate or consult , two players share every mark
     },
     Move {
         room_id:String,
-        position:usize
+        #[serde(flatten)]
+        mv:MovePayload //whatever the room's game takes , e.g. {"position":4}
+    },
+    //quantum rooms , a spooky mark in two cells
+    SpookyMove{
+        room_id:String,
+        cells:[usize;2]
+    },
+    //quantum rooms , where the mark that closed a cycle lands
+    Collapse{
+        room_id:String,
+        cell:usize
+    },
+    //consult teams , play the move a teammate proposed
+    ConfirmMove{
+        room_id:String
+    },
+    //consult teams , turn down the proposed move (or take back your own)
+    RejectMove{
+        room_id:String
+    },
+    Chat{
+        room_id:String,
+        text:String,
+        #[serde(default)]
+        team:bool //only to your teammates
     },
     Leave{
         room_id :String
+    },
+    //give up the game but stay in the room (to chat , or see the review)
+    Resign{
+        room_id:String
+    },
+    LobbySubscribe,
+    LobbyUnsubscribe,
+    WatchReplay{
+        room_id:String,
+        #[serde(default)]
+        speed:Option<f64> //1.0 plays the moves with their real timing , 2.0 twice as fast
+    },
+    StopReplay,
+    Hint{
+        room_id:String
     }
 }
 
@@ -129,21 +314,43 @@ impl StreamHandler<Result<ws::Message,ws::ProtocolError>> for WsClient{
                 //try to parse the JSON as a clientCMD
                 match serde_json::from_str::<ClientCmd>(&text){
                     Ok(cmd)=>match cmd {
-                        ClientCmd::Join { room_id }=>{
+                        ClientCmd::Join { room_id, invite_code, password, hints, bot, game, rules, players, teams }=>{
                             // Parse room_id string to UUID (if provided)
                             let room_uuid = room_id.and_then(|s|Uuid::parse_str(&s).ok());
-
-                            let join = JoinRoom{
-                                room_id:room_uuid,
-                                user_id:self.user_id,
-                                addr:ctx.address() //my websocket actors address
-                            };
+                            let user_id = self.user_id;
+                            let addr = ctx.address(); //my websocket actors address
 
                             let mgr = self.room_mgr.clone();
+                            let db = self.db.clone();
                             //this start the asyn block that 
                             //run independent , can use await inside ,captures(move) variable from outer scope (mgr,join,user_id) 
                               // Spawn the future on the actor's context
                             async move {
+                                //invite codes live in the db , resolve them first so the room manager only sees the room
+                                let invite = match invite_code {
+                                    Some(code) => match resolve_invite(&db, &code, user_id, password.clone()).await {
+                                        Ok(invite) => Some(invite),
+                                        Err(e) => return Ok(Err(e))
+                                    },
+                                    None => None
+                                };
+
+                                //shown next to the room in the lobby , players without an account have none
+                                let rating = db.get_user_by_id(user_id).await.ok().map(|u|u.rating);
+                                let join = JoinRoom{
+                                    room_id:room_uuid,
+                                    user_id,
+                                    addr,
+                                    rating,
+                                    password,
+                                    invite,
+                                    hints,
+                                    bot,
+                                    game,
+                                    rules,
+                                    players,
+                                    teams
+                                };
                                 mgr.send(join).await
                             }
                             .into_actor(self)  //Convert future into an actor future This tells Actix:“This async future belongs to THIS actor (WsClient). Run it on the actor’s event loop.”
@@ -173,41 +380,30 @@ impl StreamHandler<Result<ws::Message,ws::ProtocolError>> for WsClient{
                             })
                             .spawn(ctx);
                         }
-                        ClientCmd::Move { room_id, position }=>{
-                            if let Ok(room_uuid) = Uuid::parse_str(&room_id){
-                                let mv = PlayerMove { 
-                                    room_id:room_uuid, 
-                                    user_id:self.user_id,
-                                    position 
-                                };
-                                let mgr = self.room_mgr.clone();
-
-                                async move {
-                                    mgr.send(mv).await
-                                }
-                                .into_actor(self)
-                                .then(move |result,act,ctx| {
-                                    if let Ok(Err(e)) = result {
-                                        let err = serde_json::json!({
-                                            "type": "error",
-                                            "message": e
-                                        })
-                                        .to_string();
-                                        ctx.text(err);
-                                    }
-                                    fut::ready(())
-                                })
-                                .spawn(ctx);
-
-                                }else {
-                                // Invalid room ID format
-                                let err = serde_json::json!({
-                                    "type": "error",
-                                    "message": "invalid room id"
-                                })
-                                .to_string();
-                                ctx.text(err);
-                            }    
+                        ClientCmd::Move { room_id, mv }=>{
+                            self.send_move(&room_id, mv, ctx);
+                        }
+                        ClientCmd::SpookyMove { room_id, cells }=>{
+                            self.send_move(&room_id, encode_payload::<QuantumState>(QuantumMove::Spooky{ cells }), ctx);
+                        }
+                        ClientCmd::Collapse { room_id, cell }=>{
+                            self.send_move(&room_id, encode_payload::<QuantumState>(QuantumMove::Collapse{ collapse:cell }), ctx);
+                        }
+                        ClientCmd::ConfirmMove { room_id }=>{
+                            let user_id = self.user_id;
+                            self.send_to_room(&room_id, |room_id| AnswerProposal{ room_id, user_id, accept:true }, ctx);
+                        }
+                        ClientCmd::RejectMove { room_id }=>{
+                            let user_id = self.user_id;
+                            self.send_to_room(&room_id, |room_id| AnswerProposal{ room_id, user_id, accept:false }, ctx);
+                        }
+                        ClientCmd::Chat { room_id, text, team }=>{
+                            let user_id = self.user_id;
+                            self.send_to_room(&room_id, |room_id| RoomChat{ room_id, user_id, text, team }, ctx);
+                        }
+                        ClientCmd::Resign { room_id }=>{
+                            let user_id = self.user_id;
+                            self.send_to_room(&room_id, |room_id| Resign{ room_id, user_id }, ctx);
                         }
                         ClientCmd::Leave { room_id }=>{
                             if let Ok(room_uuid) = Uuid::parse_str(&room_id){
@@ -231,7 +427,109 @@ impl StreamHandler<Result<ws::Message,ws::ProtocolError>> for WsClient{
                                 }).to_string();
                                 ctx.text(err);
                             }
-                        }  
+                        }
+                        ClientCmd::LobbySubscribe=>{
+                            self.room_mgr.do_send(SubscribeLobby{
+                                user_id:self.user_id,
+                                addr:ctx.address()
+                            });
+                            self.in_lobby = true;
+                        }
+                        ClientCmd::LobbyUnsubscribe=>{
+                            self.room_mgr.do_send(UnsubscribeLobby{
+                                user_id:self.user_id
+                            });
+                            self.in_lobby = false;
+                        }
+                        ClientCmd::WatchReplay { room_id, speed }=>{
+                            let speed = speed.unwrap_or(1.0);
+                            let Ok(room_uuid) = Uuid::parse_str(&room_id) else {
+                                let err = serde_json::json!({
+                                    "type":"error",
+                                    "message":"invalid room id"
+                                }).to_string();
+                                ctx.text(err);
+                                return;
+                            };
+                            if !REPLAY_SPEEDS.contains(&speed) {
+                                let err = serde_json::json!({
+                                    "type":"error",
+                                    "message":format!("speed must be between {} and {}", REPLAY_SPEEDS.start(), REPLAY_SPEEDS.end())
+                                }).to_string();
+                                ctx.text(err);
+                                return;
+                            }
+
+                            let db = self.db.clone();
+                            async move {
+                                let events = db.get_events(room_uuid).await.map_err(|_|"failed to load game".to_string())?;
+                                match GameReplay::from_events(room_uuid, &events) {
+                                    Ok(Some(replay)) if replay.status.is_finished() => Ok(replay),
+                                    Ok(Some(_)) => Err("game is still in progress".to_string()),
+                                    Ok(None) => Err("game not found".to_string()),
+                                    Err(_) => Err("failed to rebuild game".to_string())
+                                }
+                            }
+                            .into_actor(self)
+                            .then(move |result, act, ctx| {
+                                match result {
+                                    Ok(replay) => act.play_replay(replay, speed, ctx),
+                                    Err(e) => {
+                                        let err = serde_json::json!({
+                                            "type": "error",
+                                            "message": e
+                                        })
+                                        .to_string();
+                                        ctx.text(err);
+                                    }
+                                }
+                                fut::ready(())
+                            })
+                            .spawn(ctx);
+                        }
+                        ClientCmd::StopReplay=>{
+                            self.stop_replay(ctx);
+                        }
+                        ClientCmd::Hint { room_id }=>{
+                            let Ok(room_uuid) = Uuid::parse_str(&room_id) else {
+                                let err = serde_json::json!({
+                                    "type":"error",
+                                    "message":"invalid room id"
+                                }).to_string();
+                                ctx.text(err);
+                                return;
+                            };
+                            let hint = RequestHint{
+                                room_id:room_uuid,
+                                user_id:self.user_id
+                            };
+                            let mgr = self.room_mgr.clone();
+
+                            async move {
+                                mgr.send(hint).await
+                            }
+                            .into_actor(self)
+                            .then(move |result,_,ctx| {
+                                let payload = match result {
+                                    Ok(Ok(analysis)) => serde_json::json!({
+                                        "type":"hint",
+                                        "room_id":room_uuid,
+                                        "analysis":analysis
+                                    }),
+                                    Ok(Err(e)) => serde_json::json!({
+                                        "type": "error",
+                                        "message": e
+                                    }),
+                                    Err(_) => serde_json::json!({
+                                        "type": "error",
+                                        "message": "internal server error"
+                                    })
+                                };
+                                ctx.text(payload.to_string());
+                                fut::ready(())
+                            })
+                            .spawn(ctx);
+                        }
                     }
                     _ =>{
                         log::warn!("Invalid JSON command from {}", self.user_id);
@@ -249,4 +547,38 @@ impl StreamHandler<Result<ws::Message,ws::ProtocolError>> for WsClient{
             
         }
     }
-}
\ No newline at end of file
+}
+
+//the room behind an invite code , a player other than the creator takes the O seat on the row first
+//so the row knows both players before any move is written to it.
+//the room password is checked here , before the seat is taken (the room manager trusts invites)
+async fn resolve_invite(db:&AnyDb, code:&str, user_id:Uuid, password:Option<String>)->Result<InvitedRoom,String>{
+    let invite = db.get_active_invite(code).await.map_err(|_|"invite not found or expired".to_string())?;
+    let mut room = db.get_room_by_room_id(invite.room_id).await.map_err(|_|"room not found".to_string())?;
+    if room.status.is_finished() {
+        return Err("this game is over".into());
+    }
+    if let Some(hash) = room.password.clone() {
+        let given = password.ok_or_else(||"incorrect room password".to_string())?;
+        match actix_web::rt::task::spawn_blocking(move || db::verify_password(&given, &hash)).await {
+            Ok(true) => {}
+            Ok(false) => return Err("incorrect room password".into()),
+            Err(_) => return Err("internal server error".into())
+        }
+    }
+    if user_id != room.player_x_id && room.player_o_id != Some(user_id) {
+        if room.player_o_id.is_some() {
+            return Err("room is full".into());
+        }
+        room = db.join_room(room.id, user_id, Some(room.version)).await.map_err(|_|"room is full".to_string())?;
+    }
+    Ok(InvitedRoom{
+        room_id:room.id,
+        game:room.game,
+        rules:room.rules,
+        fresh:room.board_state == room.game.empty_cells(),
+        password:room.password,
+        player_x_id:room.player_x_id,
+        version:room.version
+    })
+}
@@ -1,7 +1,8 @@
 use actix::Actor;
-use actix_web::{App, HttpServer, HttpRequest, HttpResponse, web, Error};
+use actix_web::{App, HttpServer, HttpRequest, HttpResponse, web};
 use actix_web_actors::ws;
 use uuid::Uuid;
+use app_config::{Config, Service};
 
 
 pub mod actors;
@@ -11,14 +12,25 @@ pub use state::*;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-
-    let room_manager_addr = RoomManager::new().start();
+    //RUST_LOG picks the level , info unless set
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let config = Config::load(Service::Ws).expect("Invalid configuration");
+    let db = db::AnyDb::connect(&config.database, config.migration_mode())
+        .await
+        .expect("Failed to connect to database");
+    let bind = config.ws.bind.clone();
+    let event_log_addr = EventLog::new(db.clone()).start();
+    let mut room_manager = RoomManager::new(event_log_addr);
+    room_manager.restore_rooms(&db).await;
+    let room_manager_addr = room_manager.start();
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(room_manager_addr.clone()))
+            .app_data(web::Data::new(db.clone()))
+            .app_data(web::Data::new(config.clone()))
             .route("/ws", web::get().to(ws_route))
     })
-    .bind(("0.0.0.0", 3000))?
+    .bind(bind)?
     .run()
     .await
 }
@@ -27,7 +39,9 @@ async fn main() -> std::io::Result<()> {
 async fn ws_route(
     req: HttpRequest, 
     stream: web::Payload, 
-    room_mgr: web::Data<actix::Addr<RoomManager>>
+    room_mgr: web::Data<actix::Addr<RoomManager>>,
+    db: web::Data<db::AnyDb>,
+    config: web::Data<Config>
 ) -> Result<HttpResponse,actix_web::Error> {
     // Parse the query string to extract user_id
     // Example: "user_id=11111111-1111-1111-1111-111111111111"
@@ -61,7 +75,7 @@ async fn ws_route(
     
     // Create a new WsClient actor for this connection
     // This actor will handle all messages for this specific client
-    let ws = WsClient::new(user_id, room_mgr.get_ref().clone());
+    let ws = WsClient::new(user_id, room_mgr.get_ref().clone(), db.get_ref().clone(), &config.ws);
     
     // Start the WebSocket actor and complete the upgrade
     // This returns an HTTP 101 Switching Protocols response
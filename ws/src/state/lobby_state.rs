@@ -0,0 +1,40 @@
+use game::{GameKind, RoomStatus, RuleSet, TeamPlay};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::Room;
+
+/// One entry of the public lobby feed.
+/// This is what lobby subscribers see for every room that can still be joined.
+/// Games have no clock , so there is no time control to show.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LobbyRoom {
+    pub room_id: Uuid,
+    pub game: GameKind,
+    pub rules: RuleSet,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teams: Option<TeamPlay>,
+    pub creator: Option<Uuid>, //player[0] , the user who opened the room
+    pub rating: Option<i32>, //the creator's rating , None when they play without an account
+    pub players: usize,
+    pub capacity: usize,
+    pub status: RoomStatus,
+    pub hints: bool,
+}
+
+impl LobbyRoom {
+    pub fn from_room(room: &Room) -> Self {
+        Self {
+            room_id: room.id,
+            game: room.game.kind(),
+            rules: room.game.rules(),
+            teams: room.teams,
+            creator: room.players.first().copied(),
+            rating: room.players.first().and_then(|u| room.ratings.get(u)).copied(),
+            players: room.players.len(),
+            capacity: room.capacity(),
+            status: room.game.status(),
+            hints: room.hints,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-pub mod game_state;
-pub use game_state::*;
 pub mod room_state;
-pub use room_state::*;
\ No newline at end of file
+pub use room_state::*;
+pub mod lobby_state;
+pub use lobby_state::*;
@@ -1,41 +1,477 @@
 use std::{collections::HashMap};
 use actix::Addr;
+use game::{GameEvent, GameState, Mark, MovePayload, Replay, RoomGame, RoomStatus, TEAM_SIZE, TeamPlay};
 use uuid::Uuid;
 
-use crate::{GameState, WsClient};
+use crate::WsClient;
+
+//a consult team's move waiting for a teammate to confirm it
+pub struct Proposal {
+    pub user_id : Uuid, //who proposed it , the move is played as theirs
+    pub seat : usize,
+    pub mv : MovePayload
+}
 
 pub struct Room {
     pub id : Uuid,
-    pub players : Vec<Uuid>, //two player max and order matters player[0] = 'X' player[1] = 'O'
+    pub players : Vec<Uuid>, //everybody at the table , in the order they joined
+    pub seats : HashMap<Uuid,usize>, //the seat each player sat down in , players who leave a started game keep theirs so they are still rated
     pub addrs : HashMap<Uuid,Addr<WsClient>>,
-    pub game :GameState
+    pub ratings : HashMap<Uuid,i32>, //ratings of the players with an account , as they were when they joined
+    pub game :Box<dyn RoomGame>, //whichever game the room hosts , tic-tac-toe unless picked otherwise
+    pub moves : Vec<MovePayload>, //moves played so far , for the review at the end
+    pub password : Option<String>, //argon2 hash , checked by the room manager before anyone joins
+    pub private : bool, //private rooms are reachable by invite/room id only and never listed in the lobby
+    pub hints : bool, //casual room , players may ask the solver for their best move
+    pub teams : Option<TeamPlay>, //team room , TEAM_SIZE players share every mark
+    pub team_moves : Vec<usize>, //moves played by each seat , whose turn it is inside an alternating team
+    pub proposal : Option<Proposal>, //consult teams , the move the team to move is deciding on
+    pub version : Option<i32> //invite rooms , version of the db row the room is in step with. their moves go through the row first
 }
 
 impl Room{
-    pub fn new(id:Uuid)->Self {
+    //seat taken by the computer opponent , it has no socket and is never in user_room
+    pub const BOT_ID: Uuid = Uuid::from_u128(0xb07);
+
+    pub fn new(id:Uuid,game:Box<dyn RoomGame>)->Self {
         Self { 
             id,
             players :Vec::new(),
+            seats : HashMap::new(),
             addrs : HashMap::new(),
-            game :GameState::new()
+            ratings : HashMap::new(),
+            game,
+            moves :Vec::new(),
+            password : None,
+            private : false,
+            hints : false,
+            teams : None,
+            team_moves : Vec::new(),
+            proposal : None,
+            version : None
          }
     }
+
+    //a room whose game was still going when the server stopped , rebuilt from its event log.
+    //everybody who was at the table keeps their seat and gets back in on reconnect ,
+    //None when the last game in the log isn't in progress.
+    //the password isn't in the log and comes back as None , it only guards taking a seat
+    //and once a game started every seat stays taken (see leave) , so nobody new gets in either way
+    pub fn restore(id:Uuid,events:&[GameEvent])->Result<Option<Self>,String>{
+        let mut replay = Replay::new();
+        let mut room = Self::new(id, Box::new(GameState::new()));
+        for event in events {
+            replay.apply(event)?;
+            match event {
+                GameEvent::Created{ private, hints, teams, .. } => {
+                    room = Self::new(id, Box::new(GameState::new()));
+                    room.private = *private;
+                    room.hints = *hints;
+                    room.teams = *teams;
+                }
+                GameEvent::Joined{ user_id, .. } => room.players.push(*user_id),
+                GameEvent::Left{ user_id } => room.players.retain(|u|u != user_id),
+                GameEvent::Moved{ mark, mv, .. } => {
+                    room.moves.push(mv.clone());
+                    room.moved(mark.seat());
+                }
+                GameEvent::Chat{ .. } | GameEvent::Resigned{ .. } => {}
+            }
+        }
+        if !replay.game.is_playing() {
+            return Ok(None);
+        }
+        room.seats = replay.players.into_iter().collect();
+        room.game = replay.game;
+        Ok(Some(room))
+    }
+
+    pub fn private(id:Uuid,game:Box<dyn RoomGame>,password:Option<String>)->Self{
+        Self {
+            password,
+            private : true,
+            ..Self::new(id,game)
+        }
+    }
+
     //get the mark for a give player
-    pub fn mark_for(&self,user:&Uuid)->Option<char>{
-        match self.players.iter().position(|u|u ==user)? {
-            0 =>Some('X'),
-            1 =>Some('O'),
-            _=>None
+    pub fn mark_for(&self,user:&Uuid)->Option<Mark>{
+        self.seat_of(user).and_then(Mark::from_seat)
+    }
+    //seat of a player still at the table
+    pub fn seat_of(&self,user:&Uuid)->Option<usize>{
+        if !self.players.contains(user) {
+            return None;
+        }
+        self.seats.get(user).copied()
+    }
+    //everybody who sat down in `seat` , players who left a started game included
+    pub fn seated(&self,seat:usize)->Vec<Uuid>{
+        self.seats.iter().filter(|(_,s)|**s == seat).map(|(u,_)|*u).collect()
+    }
+    //`user` takes the seat with the fewest players (the lowest one on a tie) , None when every seat is taken
+    pub fn sit(&mut self,user:Uuid)->Option<usize>{
+        let seat = (0..self.game.player_count())
+            .filter(|s|self.seated(*s).len() < self.team_size())
+            .min_by_key(|s|self.seated(*s).len())?;
+        self.sit_in(user, seat)
+    }
+    //`user` takes `seat` , None when it is already taken
+    pub fn sit_in(&mut self,user:Uuid,seat:usize)->Option<usize>{
+        if seat >= self.game.player_count() || self.seated(seat).len() >= self.team_size() {
+            return None;
+        }
+        self.players.push(user);
+        self.seats.insert(user, seat);
+        Some(seat)
+    }
+    //`user` gets up , their seat is only freed while the game hasn't started
+    //returns the seat they were playing
+    pub fn leave(&mut self,user:&Uuid)->Option<usize>{
+        let seat = self.seat_of(user);
+        self.players.retain(|u|u != user);
+        self.addrs.remove(user);
+        if self.game.status() == RoomStatus::Waiting {
+            self.seats.remove(user);
+        }
+        seat
+    }
+    //players sharing each seat
+    pub fn team_size(&self)->usize{
+        if self.teams.is_some() { TEAM_SIZE } else { 1 }
+    }
+    pub fn capacity(&self)->usize{
+        self.game.player_count() * self.team_size()
+    }
+    //everybody still at the table playing `seat` , in the order they joined
+    pub fn team(&self,seat:usize)->Vec<Uuid>{
+        self.players.iter().filter(|u|self.seat_of(u) == Some(seat)).copied().collect()
+    }
+    //the player who makes the next move , None when any member of the team to move may propose it
+    pub fn next_player(&self)->Option<Uuid>{
+        let seat = self.game.to_move();
+        let team = self.team(seat);
+        match self.teams {
+            Some(TeamPlay::Consult) => None,
+            Some(TeamPlay::Alternate) if !team.is_empty() => {
+                let played = self.team_moves.get(seat).copied().unwrap_or(0);
+                Some(team[played % team.len()])
+            }
+            _ => team.first().copied()
         }
     }
+    //a move was played for `seat` , the next member of an alternating team is up
+    pub fn moved(&mut self,seat:usize){
+        if self.team_moves.len() <= seat {
+            self.team_moves.resize(seat + 1, 0);
+        }
+        self.team_moves[seat] += 1;
+        self.proposal = None;
+    }
+
+    //the room's id , game kind and rule set next to the serialized game state (board , turn , status , ...) ,
+    //every message about the game starts from this , `user` only gets what their seat may see
+    pub fn state_message(&self,user:&Uuid)->serde_json::Map<String,serde_json::Value>{
+        let mut message = match self.game.state_for(self.seat_of(user)) {
+            serde_json::Value::Object(state) => state,
+            _ => serde_json::Map::new()
+        };
+        message.insert("room_id".into(), self.id.to_string().into());
+        message.insert("game".into(), self.game.kind().as_str().into());
+        message.insert("rules".into(), self.game.rules().as_str().into());
+        if let Some(teams) = self.teams {
+            let seats:Vec<Vec<Uuid>> = (0..self.game.player_count()).map(|s|self.team(s)).collect();
+            message.insert("teams".into(), serde_json::json!(seats));
+            message.insert("team_play".into(), teams.as_str().into());
+            message.insert("next_player".into(), serde_json::json!(self.next_player()));
+        }
+        message
+    }
+
+    //the game hides the other players' moves right now , the fog lifts when it ends
+    pub fn hides_moves(&self)->bool{
+        self.game.rules().hidden() && self.game.is_playing()
+    }
+
+    pub fn has_bot(&self)->bool{
+        self.players.contains(&Self::BOT_ID)
+    }
+    //the game is on and the seat to move belongs to the bot
+    pub fn bot_to_move(&self)->bool{
+        self.game.is_playing() && self.seat_of(&Self::BOT_ID) == Some(self.game.to_move())
+    }
+    //nobody left but the bot , the room can go
+    pub fn only_bots(&self)->bool{
+        self.players.iter().all(|u|*u == Self::BOT_ID)
+    }
 
-    pub fn is_full(&mut self)->bool{
-        self.players.len() >= 2 
+    pub fn is_full(&self)->bool{
+        self.seats.len() >= self.capacity()
+    }
+    //a room is listed in the lobby while nobody has started playing and there is a free seat
+    pub fn is_open(&self)->bool{
+        !self.private && self.game.status() == RoomStatus::Waiting && !self.is_full()
     }
-    //start the game if we have 2 player
+    //start the game once every seat is taken
      pub fn start_game_if_ready(&mut self) {
-        if self.players.len() == 2 && self.game.status == "waiting" {
-            self.game.status = "playing".into();
+        if self.is_full() {
+            self.game.start();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::{GameKind, RuleSet};
+    use serde_json::{Value, json};
+
+    const X : Uuid = Uuid::from_u128(1);
+    const O : Uuid = Uuid::from_u128(2);
+    const WATCHER : Uuid = Uuid::from_u128(3);
+    //the second players of team rooms , TEAM_SIZE is 2
+    const X2 : Uuid = Uuid::from_u128(4);
+    const O2 : Uuid = Uuid::from_u128(5);
+
+    fn payload(value:Value)->MovePayload{
+        match value {
+            Value::Object(fields) => fields,
+            _ => unreachable!()
+        }
+    }
+
+    //a started room with every seat taken by `users` in order
+    fn started(rules:RuleSet,users:&[Uuid])->Room{
+        team_room(rules, None, users)
+    }
+
+    fn team_room(rules:RuleSet,teams:Option<TeamPlay>,users:&[Uuid])->Room{
+        let mut room = Room::new(Uuid::nil(), GameKind::TicTacToe.new_game(rules, None).unwrap());
+        room.teams = teams;
+        for user in users {
+            room.sit(*user).unwrap();
+        }
+        room.start_game_if_ready();
+        room
+    }
+
+    fn play(room:&mut Room,seat:usize,position:usize){
+        let mv = room.game.play(seat, &payload(json!({"position":position}))).unwrap();
+        room.moves.push(mv);
+        room.moved(seat);
+    }
+
+    //the board as `user` is sent it
+    fn board(room:&Room,user:&Uuid)->Value{
+        room.state_message(user)["board"].clone()
+    }
+
+    #[test]
+    fn fog_rooms_only_send_each_seat_its_own_marks(){
+        let mut room = started(RuleSet::Fog, &[X,O]);
+        play(&mut room, 0, 0);
+        play(&mut room, 1, 4);
+        assert!(room.hides_moves());
+
+        assert_eq!(board(&room, &X), json!(["X",null,null,null,null,null,null,null,null]));
+        assert_eq!(board(&room, &O), json!([null,null,null,null,"O",null,null,null,null]));
+        assert_eq!(board(&room, &WATCHER), json!([null,null,null,null,null,null,null,null,null]));
+        //nothing the opponent bumped into either
+        assert_eq!(room.state_message(&X)["revealed"], json!([[],[]]));
+
+        //a player who left is a watcher now
+        room.leave(&O);
+        assert_eq!(board(&room, &O), board(&room, &WATCHER));
+    }
+
+    #[test]
+    fn the_fog_lifts_for_everybody_at_the_end(){
+        let mut room = started(RuleSet::FogRetry, &[X,O]);
+        for (seat, position) in [(0,0),(1,3),(0,1),(1,4),(0,2)] {
+            play(&mut room, seat, position);
+        }
+        assert!(!room.hides_moves());
+        let everything = json!(["X","X","X","O","O",null,null,null,null]);
+        for user in [X,O,WATCHER] {
+            assert_eq!(board(&room, &user), everything);
+        }
+    }
+
+    #[test]
+    fn open_boards_hide_nothing(){
+        let mut room = started(RuleSet::Standard, &[X,O]);
+        play(&mut room, 0, 4);
+        assert!(!room.hides_moves());
+        assert_eq!(room.state_message(&O)["board"][4], json!("X"));
+        assert_eq!(room.state_message(&WATCHER)["board"][4], json!("X"));
+    }
+
+    #[test]
+    fn team_players_fill_the_emptiest_seat(){
+        let mut room = Room::new(Uuid::nil(), Box::new(GameState::new()));
+        room.teams = Some(TeamPlay::Alternate);
+        assert_eq!(room.capacity(), 4);
+        assert_eq!(room.sit(X), Some(0));
+        assert_eq!(room.sit(O), Some(1));
+        //O2 asks for the O seat , the next one to sit gets what is left
+        assert_eq!(room.sit_in(O2, 1), Some(1));
+        assert_eq!(room.sit_in(WATCHER, 1), None);
+        assert_eq!(room.sit_in(WATCHER, 2), None);
+        assert!(!room.is_full());
+        assert_eq!(room.sit(X2), Some(0));
+        assert_eq!(room.sit(WATCHER), None);
+        assert_eq!(room.team(0), vec![X,X2]);
+        assert_eq!(room.team(1), vec![O,O2]);
+
+        room.start_game_if_ready();
+        assert!(room.game.is_playing());
+        assert_eq!(room.state_message(&X)["teams"], json!([[X,X2],[O,O2]]));
+    }
+
+    #[test]
+    fn one_player_per_seat_without_teams(){
+        let mut room = Room::new(Uuid::nil(), Box::new(GameState::new()));
+        assert_eq!(room.sit(X), Some(0));
+        assert_eq!(room.sit_in(O, 0), None);
+        assert_eq!(room.sit(O), Some(1));
+        assert_eq!(room.sit(WATCHER), None);
+        room.start_game_if_ready();
+        assert_eq!(room.next_player(), Some(X));
+        play(&mut room, 0, 4);
+        assert_eq!(room.next_player(), Some(O));
+    }
+
+    #[test]
+    fn alternating_teams_take_their_moves_in_turn(){
+        let mut room = team_room(RuleSet::Standard, Some(TeamPlay::Alternate), &[X,O,X2,O2]);
+        let mut order = Vec::new();
+        for position in [0,1,2,4,3,5] {
+            let player = room.next_player().unwrap();
+            order.push(player);
+            let seat = room.seat_of(&player).unwrap();
+            play(&mut room, seat, position);
+        }
+        assert_eq!(order, [X,O,X2,O2,X,O]);
+        assert_eq!(room.team_moves, [3,3]);
+        assert_eq!(room.state_message(&O)["next_player"], json!(X2));
+    }
+
+    #[test]
+    fn consulting_teams_have_no_single_next_player(){
+        let mut room = team_room(RuleSet::Standard, Some(TeamPlay::Consult), &[X,O,X2,O2]);
+        assert_eq!(room.next_player(), None);
+        room.proposal = Some(Proposal{ user_id : X2, seat : 0, mv : payload(json!({"position":4})) });
+        //the move is played once a teammate confirms , which clears the proposal
+        play(&mut room, 0, 4);
+        assert!(room.proposal.is_none());
+        assert_eq!(room.next_player(), None);
+        assert_eq!(room.state_message(&X)["team_play"], json!("consult"));
+    }
+
+    #[test]
+    fn leaving_keeps_the_seat_once_the_game_started(){
+        let mut room = Room::new(Uuid::nil(), Box::new(GameState::new()));
+        room.sit(X);
+        assert_eq!(room.leave(&X), Some(0));
+        //nothing started , the seat is free again
+        assert!(room.seated(0).is_empty());
+        assert_eq!(room.sit(O), Some(0));
+        assert_eq!(room.sit(X), Some(1));
+
+        room.start_game_if_ready();
+        assert_eq!(room.leave(&X), Some(1));
+        assert_eq!(room.seat_of(&X), None);
+        assert_eq!(room.mark_for(&X), None);
+        //still rated for seat 1 and nobody can take it over
+        assert_eq!(room.seated(1), vec![X]);
+        assert!(room.is_full());
+        assert_eq!(room.sit(WATCHER), None);
+        assert!(room.team(1).is_empty());
+    }
+
+    #[test]
+    fn alternating_teams_skip_players_who_left(){
+        let mut room = team_room(RuleSet::Standard, Some(TeamPlay::Alternate), &[X,O,X2,O2]);
+        room.leave(&X2);
+        for position in [0,1,2,4] {
+            let player = room.next_player().unwrap();
+            assert_ne!(player, X2);
+            let seat = room.seat_of(&player).unwrap();
+            play(&mut room, seat, position);
+        }
+        assert_eq!(room.next_player(), Some(X));
+    }
+
+    //a live room that logs its events the way the room manager does
+    fn logged(teams:Option<TeamPlay>,password:Option<String>)->(Room,Vec<GameEvent>){
+        let mut room = Room::private(Uuid::nil(), Box::new(GameState::new()), password);
+        room.hints = true;
+        room.teams = teams;
+        let events = vec![GameEvent::Created{
+            private:room.private,
+            hints:room.hints,
+            game:room.game.kind(),
+            rules:room.game.rules(),
+            players:None,
+            teams:room.teams
+        }];
+        (room, events)
+    }
+
+    fn join(room:&mut Room,events:&mut Vec<GameEvent>,user:Uuid){
+        let mark = room.sit(user).and_then(Mark::from_seat).unwrap();
+        events.push(GameEvent::Joined{ user_id:user, mark });
+        room.start_game_if_ready();
+    }
+
+    fn logged_move(room:&mut Room,events:&mut Vec<GameEvent>,position:usize){
+        let user_id = room.next_player().unwrap();
+        let seat = room.seat_of(&user_id).unwrap();
+        play(room, seat, position);
+        let mv = room.moves.last().unwrap().clone();
+        events.push(GameEvent::Moved{ user_id, mark:Mark::from_seat(seat).unwrap(), mv });
+    }
+
+    #[test]
+    fn restoring_a_room_gives_back_the_live_one(){
+        let (mut live, mut events) = logged(Some(TeamPlay::Alternate), None);
+        for user in [X,O,X2,O2] {
+            join(&mut live, &mut events, user);
+        }
+        for position in [4,0,8] {
+            logged_move(&mut live, &mut events, position);
+        }
+        live.leave(&O2);
+        events.push(GameEvent::Left{ user_id:O2 });
+        events.push(GameEvent::Chat{ user_id:X, text:"gg".into() });
+
+        let restored = Room::restore(live.id, &events).unwrap().unwrap();
+        assert_eq!(restored.players, live.players);
+        assert_eq!(restored.seats, live.seats);
+        assert_eq!(restored.team_moves, live.team_moves);
+        assert_eq!(restored.moves, live.moves);
+        assert_eq!(restored.game.state(), live.game.state());
+        assert_eq!((restored.private, restored.hints, restored.teams), (live.private, live.hints, live.teams));
+        assert_eq!(restored.next_player(), live.next_player());
+        assert_eq!(restored.next_player(), Some(O));
+    }
+
+    #[test]
+    fn restored_rooms_stay_closed_without_their_password(){
+        let (mut live, mut events) = logged(None, Some("argon2 hash".into()));
+        join(&mut live, &mut events, X);
+        join(&mut live, &mut events, O);
+        logged_move(&mut live, &mut events, 4);
+
+        let mut restored = Room::restore(live.id, &events).unwrap().unwrap();
+        assert_eq!(restored.password, None);
+        assert!(restored.private && !restored.is_open());
+        assert_eq!(restored.sit(WATCHER), None);
+        //a finished game isn't brought back at all
+        for position in [0,2,6,1,5,3,7,8] {
+            logged_move(&mut live, &mut events, position);
         }
+        assert!(!live.game.is_playing());
+        assert!(Room::restore(live.id, &events).unwrap().is_none());
     }
 }